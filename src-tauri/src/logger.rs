@@ -0,0 +1,66 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 打包后的应用没有终端，保留最近的日志供用户在界面中复制诊断信息
+const MAX_LOG_ENTRIES: usize = 500;
+
+static LOG_BUFFER: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+  Info,
+  Warn,
+  Error,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+  pub timestamp: u64,
+  pub level: LogLevel,
+  pub message: String,
+}
+
+pub fn info(message: impl Into<String>) {
+  push(LogLevel::Info, message.into());
+}
+
+pub fn warn(message: impl Into<String>) {
+  push(LogLevel::Warn, message.into());
+}
+
+pub fn error(message: impl Into<String>) {
+  push(LogLevel::Error, message.into());
+}
+
+pub fn recent(limit: usize) -> Vec<LogEntry> {
+  let Ok(buffer) = LOG_BUFFER.lock() else {
+    return Vec::new();
+  };
+  let skip = buffer.len().saturating_sub(limit);
+  buffer.iter().skip(skip).cloned().collect()
+}
+
+fn push(level: LogLevel, message: String) {
+  #[cfg(debug_assertions)]
+  eprintln!("[{level:?}] {message}");
+
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or(0);
+
+  if let Ok(mut buffer) = LOG_BUFFER.lock() {
+    if buffer.len() == MAX_LOG_ENTRIES {
+      buffer.pop_front();
+    }
+    buffer.push_back(LogEntry {
+      timestamp,
+      level,
+      message,
+    });
+  }
+}
@@ -1,88 +1,530 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use serde::Serialize;
+use logger::LogEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::thread;
+#[cfg(target_os = "macos")]
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+#[cfg(target_os = "macos")]
 use tauri::Manager;
 
+// Only the macOS backend writes log entries; other platforms just expose the empty buffer.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+mod logger;
+
 #[cfg(target_os = "macos")]
 mod platform;
 
+#[cfg(not(target_os = "macos"))]
+mod platform_unsupported;
+
+#[cfg(target_os = "windows")]
+mod platform_windows;
+
+#[cfg(target_os = "windows")]
+use platform_windows as platform;
+
+#[cfg(target_os = "linux")]
+mod platform_linux;
+
+#[cfg(target_os = "linux")]
+use platform_linux as platform;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+use platform_unsupported as platform;
+
+use platform::{
+  add_extension_inner, add_extensions_inner, apply_profile_inner, apply_saved_profile_inner,
+  backup_launch_services_inner, check_app_supports_extension_inner, check_duti_inner,
+  check_full_disk_access_detailed_inner, check_full_disk_access_inner, clear_bundle_cache_inner,
+  content_type_for_path_inner, delete_handler_entry_inner, delete_profile_inner,
+  detect_conflicts_inner, diagnose_extension_inner, export_associations_inner,
+  export_associations_to_file_inner, export_duti_config_inner, get_app_icon_inner,
+  get_association_for_extension_inner, get_bundle_info_inner, get_change_history_inner,
+  get_content_type_for_extension_inner, get_default_browser_inner, get_default_mail_client_inner,
+  import_associations_from_file_inner, import_associations_inner, import_duti_config_inner,
+  inspect_file_inner, list_all_handlers_for_extension_inner,
+  list_candidate_apps_for_extension_inner, list_file_associations_inner,
+  list_file_associations_streaming_inner, list_file_associations_with_icons_inner,
+  list_handlers_for_extension_inner, list_installed_applications_inner,
+  list_launch_services_backups_inner, list_pinned_extensions_inner, list_profiles_inner,
+  list_raw_handlers_inner, list_search_roots_inner, list_tracked_extensions_inner,
+  list_url_scheme_handlers_inner, open_file_with_application_inner,
+  open_full_disk_access_settings_inner, open_with_inner, pin_extension_inner,
+  preview_set_default_application_inner, rebuild_launch_services_inner,
+  remove_extension_and_handler_inner, remove_extension_inner, reset_all_to_system_defaults_inner,
+  reset_extension_to_system_default_inner, restore_launch_services_backup_inner,
+  reveal_in_finder_inner, save_profile_inner, search_applications_inner,
+  set_content_type_mapping_inner, set_default_application_by_bundle_id_inner,
+  set_default_application_for_category_inner, set_default_application_for_content_type_inner,
+  set_default_application_for_extension_inner, set_default_application_for_extensions_inner,
+  set_default_application_for_url_scheme_inner, set_default_browser_inner,
+  set_default_mail_client_inner, set_extension_note_inner, set_pinned_extensions_inner,
+  set_search_roots_inner, undo_last_change_inner, unpin_extension_inner, validate_application_inner,
+  verify_association_inner,
+};
+
 #[cfg(target_os = "macos")]
 use platform::{
-  add_extension_inner, check_full_disk_access_inner, list_file_associations_inner,
-  open_full_disk_access_settings_inner, set_default_application_for_extension_inner,
+  is_own_launch_services_write_inner, is_within_own_write_grace_inner,
+  launch_services_modified_inner,
 };
 
-#[cfg(not(target_os = "macos"))]
-mod platform {
-  use super::{FileAssociation, DEFAULT_EXTENSIONS};
+// File extensions we care about by default, grouped by the category shown in the UI.
+// This table is the single source for the built-in list. Keep in sync with the frontend list.
+const EXTENSION_CATEGORIES: &[(&str, &[&str])] = &[
+  ("Documents", &["doc", "docx", "xls", "xlsx", "ppt", "pptx", "pdf", "txt", "md", "markdown"]),
+  ("Images", &["png", "jpg", "jpeg", "gif"]),
+  ("Media", &["mp3", "mp4", "mov", "avi"]),
+  ("Archives", &["zip", "rar", "7z", "tar", "gz"]),
+  ("Web", &["html", "htm", "css", "js", "ts", "jsx", "tsx"]),
+  ("Data", &["csv", "json", "xml", "yaml", "yml", "toml"]),
+  ("Code", &["py", "java", "cpp", "c", "h", "hpp"]),
+  ("Scripts", &["sh", "bash", "zsh", "fish"]),
+  ("Misc", &["sql", "db", "sqlite", "log", "ini", "cfg", "conf"]),
+  ("Dev", &["dockerfile", "gitignore", "env", "key", "pem", "crt"]),
+];
 
-  pub fn check_full_disk_access_inner() -> Result<bool, String> {
-    Ok(true)
-  }
+// One-click presets: each group maps some categories to a single app the user picks.
+// Adding a profile only needs a new entry here.
+type ProfileGroupSpec = (&'static str, &'static [&'static str]);
+const BUILTIN_PROFILES: &[(&str, &[ProfileGroupSpec])] = &[
+  ("Developer", &[("editor", &["Web", "Data", "Code", "Scripts", "Dev"])]),
+  ("Media", &[("imageViewer", &["Images"]), ("player", &["Media"])]),
+];
 
-  pub fn open_full_disk_access_settings_inner() -> Result<(), String> {
-    Err("仅支持在 macOS 上打开系统设置".into())
-  }
+// Category for extensions the user added themselves.
+const CUSTOM_CATEGORY: &str = "Custom";
+
+// URL schemes whose default handler is listed alongside file extensions.
+const DEFAULT_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "ftp", "webcal", "tel", "maps"];
+
+// How often the background watcher re-probes full disk access.
+#[cfg(target_os = "macos")]
+const FULL_DISK_ACCESS_POLL_INTERVAL: Duration = Duration::from_secs(3);
+#[cfg(target_os = "macos")]
+const FULL_DISK_ACCESS_CHANGED_EVENT: &str = "full-disk-access-changed";
+// How often the background watcher checks the LaunchServices plist for outside changes.
+#[cfg(target_os = "macos")]
+const ASSOCIATIONS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+#[cfg(target_os = "macos")]
+const ASSOCIATIONS_CHANGED_EVENT: &str = "associations-changed";
+const LAUNCH_SERVICES_REBUILD_PROGRESS_EVENT: &str = "launch-services-rebuild-progress";
+const ASSOCIATION_RESOLVED_EVENT: &str = "association-resolved";
+const ASSOCIATION_COMPLETE_EVENT: &str = "association-complete";
+const LIST_PROGRESS_EVENT: &str = "list-progress";
 
-  pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
-    Ok(
-      DEFAULT_EXTENSIONS
+// Indeterminate means none of the probe files exist, so access can't be determined either way.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FullDiskAccessStatus {
+  Granted,
+  Denied,
+  Indeterminate,
+}
+
+// Maps to LSHandlerRoleAll / LSHandlerRoleViewer / LSHandlerRoleEditor.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HandlerRole {
+  #[default]
+  All,
+  Viewer,
+  Editor,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeOutcome {
+  Readable,
+  PermissionDenied,
+  Absent,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FullDiskAccessProbe {
+  pub path: String,
+  pub outcome: ProbeOutcome,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FullDiskAccessReport {
+  pub status: FullDiskAccessStatus,
+  pub probes: Vec<FullDiskAccessProbe>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AssociationStatus {
+  Resolved,
+  HandlerMissing,
+  AppNotFound,
+  #[default]
+  Unset,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AssociationSource {
+  UserOverride,
+  SystemDefault,
+  #[default]
+  None,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAssociation {
+  pub extension: String,
+  pub bundle_identifier: String,
+  pub application_name: String,
+  pub application_path: String,
+  pub icon_base64: Option<String>,
+  pub status: AssociationStatus,
+  pub category: String,
+  pub source: AssociationSource,
+  pub is_user_override: bool,
+  pub system_default_name: Option<String>,
+  pub is_pinned: bool,
+  pub note: Option<String>,
+  pub viewer_bundle_id: Option<String>,
+  pub editor_bundle_id: Option<String>,
+  pub content_type: Option<String>,
+}
+
+fn category_for_extension(extension: &str) -> String {
+  EXTENSION_CATEGORIES
+    .iter()
+    .find(|(_, extensions)| extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension)))
+    .map(|(category, _)| *category)
+    .unwrap_or(CUSTOM_CATEGORY)
+    .to_string()
+}
+
+fn builtin_profiles() -> Vec<BuiltinProfile> {
+  BUILTIN_PROFILES
+    .iter()
+    .map(|(name, groups)| BuiltinProfile {
+      name: name.to_string(),
+      groups: groups
         .iter()
-        .map(|ext| FileAssociation {
-          extension: ext.to_string(),
-          application_name: "Unsupported platform".into(),
-          application_path: String::new(),
+        .map(|(key, categories)| ProfileGroup {
+          key: key.to_string(),
+          categories: categories.iter().map(|category| category.to_string()).collect(),
+          extensions: EXTENSION_CATEGORIES
+            .iter()
+            .filter(|(category, _)| categories.contains(category))
+            .flat_map(|(_, extensions)| extensions.iter().map(|ext| ext.to_string()))
+            .collect(),
         })
         .collect(),
-    )
-  }
+    })
+    .collect()
+}
 
-  pub fn add_extension_inner(_extension: String) -> Result<Vec<FileAssociation>, String> {
-    list_file_associations_inner()
-  }
+fn default_extensions() -> impl Iterator<Item = &'static str> {
+  EXTENSION_CATEGORIES
+    .iter()
+    .flat_map(|(_, extensions)| extensions.iter().copied())
+}
 
-  pub fn set_default_application_for_extension_inner(
-    _extension: String,
-    _application_path: String,
-  ) -> Result<(), String> {
-    Err("仅支持在 macOS 上修改默认应用".into())
-  }
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApplyStatus {
+  Applied,
+  PendingRelaunch,
+  NoChange,
+  // The app doesn't declare support for the type; retry with force after the user confirms.
+  NeedsConfirmation,
+  // The OS settings page was opened and the user has to finish the change there (Windows).
+  ManualConfirmation,
 }
 
-// File extensions we care about by default. Keep in sync with the frontend list.
-const DEFAULT_EXTENSIONS: &[&str] = &[
-  // Documents
-  "doc", "docx", "xls", "xlsx", "ppt", "pptx", "pdf", "txt", "md", "markdown",
-  // Images
-  "png", "jpg", "jpeg", "gif",
-  // Media
-  "mp3", "mp4", "mov", "avi",
-  // Archives
-  "zip", "rar", "7z", "tar", "gz",
-  // Web
-  "html", "htm", "css", "js", "ts", "jsx", "tsx",
-  // Data / config
-  "csv", "json", "xml", "yaml", "yml", "toml",
-  // Code
-  "py", "java", "cpp", "c", "h", "hpp",
-  // Scripts
-  "sh", "bash", "zsh", "fish",
-  // DB / logs / misc
-  "sql", "db", "sqlite", "log", "ini", "cfg", "conf",
-  // Dev files
-  "dockerfile", "gitignore", "env", "key", "pem", "crt",
-];
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInspection {
+  pub extension: Option<String>,
+  pub content_type: String,
+  pub application_name: String,
+  pub application_path: String,
+  pub is_managed: bool,
+}
 
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct FileAssociation {
+pub struct AssociationProgress {
+  pub index: usize,
+  pub total: usize,
+  pub association: FileAssociation,
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ListProgress {
+  pub done: usize,
+  pub total: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssociationStreamComplete {
+  pub total: usize,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDefaultResult {
+  pub status: ApplyStatus,
+  pub detail: Option<String>,
+  pub refreshed: bool,
+}
+
+// expectedBundleId comes from LSHandlers, actualBundleId is what LaunchServices actually returns.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssociationVerification {
   pub extension: String,
+  pub content_type: String,
+  pub expected_bundle_id: Option<String>,
+  pub actual_bundle_id: Option<String>,
+  pub effective: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinProfile {
+  pub name: String,
+  pub groups: Vec<ProfileGroup>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileGroup {
+  pub key: String,
+  pub categories: Vec<String>,
+  pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionOutcome {
+  pub extension: String,
+  pub applied: bool,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSetResult {
+  pub associations: Vec<FileAssociation>,
+  pub results: Vec<ExtensionOutcome>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AddExtensionStatus {
+  Added,
+  AlreadyAdded,
+  Rejected,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AddExtensionOutcome {
+  pub input: String,
+  pub extension: String,
+  pub status: AddExtensionStatus,
+  pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AddExtensionsResult {
+  pub associations: Vec<FileAssociation>,
+  pub results: Vec<AddExtensionOutcome>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveExtensionResult {
+  pub associations: Vec<FileAssociation>,
+  pub handler_removed: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssociationChange {
+  pub extension: String,
+  pub current_bundle_identifier: Option<String>,
+  pub new_bundle_identifier: String,
+  pub new_application_path: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedEntry {
+  pub extension: String,
+  pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+  pub changes: Vec<AssociationChange>,
+  pub skipped: Vec<SkippedEntry>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchServicesBackup {
+  pub name: String,
+  pub path: String,
+  pub created_at: u64,
+}
+
+// Each stage is reported separately; failed stages land in errors so the report can be attached
+// to a bug report as-is.
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionDiagnosis {
+  pub extension: String,
+  pub normalized: Option<String>,
+  pub content_type: Option<String>,
+  pub handler_found: bool,
+  pub handler_bundle_id: Option<String>,
+  pub system_default_bundle_id: Option<String>,
+  pub application_path: Option<String>,
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedProfile {
+  pub name: String,
+  pub path: String,
+  pub created_at: u64,
+  pub association_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRecord {
+  pub timestamp: u64,
+  pub extension: String,
+  pub previous_bundle_id: Option<String>,
+  // What LaunchServices used before the change; undo restores it when the plist had no entry.
+  #[serde(default)]
+  pub previous_live_bundle_id: Option<String>,
+  pub bundle_id: String,
+  pub application_path: String,
+  // Records written by older versions have no role; treat them as All.
+  #[serde(default)]
+  pub role: HandlerRole,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateApplication {
+  pub application_name: String,
+  pub application_path: String,
+  pub bundle_identifier: String,
+  pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RawHandlerEntry {
+  pub index: usize,
+  pub content_tag: Option<String>,
+  pub content_tag_class: Option<String>,
+  pub content_type: Option<String>,
+  pub role_all: Option<String>,
+  pub role_viewer: Option<String>,
+  pub role_editor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlerSelector {
+  pub content_tag: Option<String>,
+  pub content_tag_class: Option<String>,
+  pub content_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+  pub content_type: String,
+  pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentTypeInfo {
+  pub extension: String,
+  pub content_type: String,
+  pub is_dynamic: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationVerdict {
+  pub valid: bool,
+  pub bundle_id: Option<String>,
+  pub declares_type: bool,
+  pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DutiStatus {
+  pub installed: bool,
+  pub path: Option<String>,
+  pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleInfo {
+  pub display_name: String,
+  pub application_path: String,
+  pub bundle_identifier: String,
+  pub version: Option<String>,
+  pub minimum_system_version: Option<String>,
+  pub in_standard_location: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlSchemeAssociation {
+  pub scheme: String,
+  pub application_name: String,
+  pub application_path: String,
+  pub status: AssociationStatus,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultHandlerRequest {
+  pub bundle_identifier: String,
+  pub applied: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledApplication {
   pub application_name: String,
   pub application_path: String,
+  pub bundle_identifier: String,
 }
 
 #[tauri::command]
-fn check_full_disk_access() -> Result<bool, String> {
+fn check_full_disk_access() -> Result<FullDiskAccessStatus, String> {
   check_full_disk_access_inner()
 }
 
@@ -92,8 +534,73 @@ fn open_full_disk_access_settings() -> Result<(), String> {
 }
 
 #[tauri::command]
-fn list_file_associations() -> Result<Vec<FileAssociation>, String> {
-  list_file_associations_inner()
+fn list_file_associations(app: AppHandle) -> Result<Vec<FileAssociation>, String> {
+  // Same walk as the streaming command, but collected into one list with a done/total event
+  // per resolved extension for the progress bar.
+  let mut associations = Vec::new();
+  list_file_associations_streaming_inner(|progress| {
+    let _ = app.emit(
+      LIST_PROGRESS_EVENT,
+      ListProgress {
+        done: progress.index + 1,
+        total: progress.total,
+      },
+    );
+    associations.push(progress.association);
+  })?;
+  Ok(associations)
+}
+
+#[tauri::command]
+fn list_file_associations_streaming(app: AppHandle) -> Result<(), String> {
+  // Resolving many extensions one by one is slow, so do it on a background thread and send
+  // each result to the UI as soon as it resolves.
+  thread::spawn(move || {
+    let result = list_file_associations_streaming_inner(|progress| {
+      let _ = app.emit(ASSOCIATION_RESOLVED_EVENT, progress);
+    });
+    let complete = match result {
+      Ok(total) => AssociationStreamComplete { total, error: None },
+      Err(err) => AssociationStreamComplete {
+        total: 0,
+        error: Some(err),
+      },
+    };
+    let _ = app.emit(ASSOCIATION_COMPLETE_EVENT, complete);
+  });
+  Ok(())
+}
+
+#[tauri::command]
+fn list_file_associations_grouped() -> Result<BTreeMap<String, Vec<FileAssociation>>, String> {
+  let mut groups: BTreeMap<String, Vec<FileAssociation>> = BTreeMap::new();
+  for association in list_file_associations_inner()? {
+    groups
+      .entry(association.category.clone())
+      .or_default()
+      .push(association);
+  }
+  Ok(groups)
+}
+
+#[tauri::command]
+fn list_builtin_profiles() -> Result<Vec<BuiltinProfile>, String> {
+  Ok(builtin_profiles())
+}
+
+#[tauri::command]
+fn get_association_for_extension(extension: String) -> Result<FileAssociation, String> {
+  get_association_for_extension_inner(extension)
+}
+
+#[tauri::command]
+fn list_handlers_for_extension(extension: String) -> Result<Vec<FileAssociation>, String> {
+  list_handlers_for_extension_inner(extension)
+}
+
+#[tauri::command]
+fn list_installed_applications() -> Result<Vec<InstalledApplication>, String> {
+  list_installed_applications_inner()
 }
 
 #[tauri::command]
@@ -101,12 +608,430 @@ fn add_extension(extension: String) -> Result<Vec<FileAssociation>, String> {
   add_extension_inner(extension)
 }
 
+#[tauri::command]
+fn remove_extension(extension: String) -> Result<Vec<FileAssociation>, String> {
+  remove_extension_inner(extension)
+}
+
 #[tauri::command]
 fn set_default_application_for_extension(
   extension: String,
   application_path: String,
+  force: Option<bool>,
+  role: Option<HandlerRole>,
+) -> Result<SetDefaultResult, String> {
+  set_default_application_for_extension_inner(
+    extension,
+    application_path,
+    force.unwrap_or(false),
+    role.unwrap_or_default(),
+  )
+}
+
+#[tauri::command]
+fn reset_extension_to_system_default(extension: String) -> Result<FileAssociation, String> {
+  reset_extension_to_system_default_inner(extension)
+}
+
+#[tauri::command]
+fn list_url_scheme_handlers() -> Result<Vec<UrlSchemeAssociation>, String> {
+  list_url_scheme_handlers_inner()
+}
+
+#[tauri::command]
+fn set_default_application_for_url_scheme(
+  scheme: String,
+  application_path: String,
 ) -> Result<(), String> {
-  set_default_application_for_extension_inner(extension, application_path)
+  set_default_application_for_url_scheme_inner(scheme, application_path)
+}
+
+#[tauri::command]
+fn list_candidate_apps_for_extension(
+  extension: String,
+) -> Result<Vec<CandidateApplication>, String> {
+  list_candidate_apps_for_extension_inner(extension)
+}
+
+#[tauri::command]
+fn set_default_application_for_extensions(
+  extensions: Vec<String>,
+  application_path: String,
+) -> Result<BatchSetResult, String> {
+  set_default_application_for_extensions_inner(extensions, application_path)
+}
+
+#[tauri::command]
+fn export_associations() -> Result<String, String> {
+  export_associations_inner()
+}
+
+#[tauri::command]
+fn import_associations(json: String, dry_run: bool) -> Result<ImportReport, String> {
+  import_associations_inner(json, dry_run)
+}
+
+#[tauri::command]
+fn list_launch_services_backups() -> Result<Vec<LaunchServicesBackup>, String> {
+  list_launch_services_backups_inner()
+}
+
+#[tauri::command]
+fn restore_launch_services_backup(backup_name: String) -> Result<Vec<FileAssociation>, String> {
+  restore_launch_services_backup_inner(backup_name)
+}
+
+#[tauri::command]
+fn export_associations_to_file(path: String) -> Result<usize, String> {
+  export_associations_to_file_inner(path)
+}
+
+#[tauri::command]
+fn import_associations_from_file(path: String, dry_run: bool) -> Result<ImportReport, String> {
+  import_associations_from_file_inner(path, dry_run)
+}
+
+#[tauri::command]
+fn list_file_associations_with_icons() -> Result<Vec<FileAssociation>, String> {
+  list_file_associations_with_icons_inner()
+}
+
+#[tauri::command]
+fn backup_launch_services() -> Result<String, String> {
+  backup_launch_services_inner()
+}
+
+#[tauri::command]
+fn clear_bundle_cache() -> Result<(), String> {
+  clear_bundle_cache_inner()
+}
+
+#[tauri::command]
+fn get_bundle_info(application_path: String) -> Result<BundleInfo, String> {
+  get_bundle_info_inner(application_path)
+}
+
+#[tauri::command]
+fn get_default_browser() -> Result<UrlSchemeAssociation, String> {
+  get_default_browser_inner()
+}
+
+#[tauri::command]
+fn set_default_browser(application_path: String) -> Result<DefaultHandlerRequest, String> {
+  set_default_browser_inner(application_path)
+}
+
+#[tauri::command]
+fn get_default_mail_client() -> Result<UrlSchemeAssociation, String> {
+  get_default_mail_client_inner()
+}
+
+#[tauri::command]
+fn set_default_mail_client(application_path: String) -> Result<DefaultHandlerRequest, String> {
+  set_default_mail_client_inner(application_path)
+}
+
+#[tauri::command]
+fn open_with(file_path: String, application_path: String) -> Result<(), String> {
+  open_with_inner(file_path, application_path)
+}
+
+#[tauri::command]
+fn rebuild_launch_services(app: AppHandle, deep: bool) -> Result<u64, String> {
+  rebuild_launch_services_inner(deep, |line| {
+    let _ = app.emit(LAUNCH_SERVICES_REBUILD_PROGRESS_EVENT, line);
+  })
+}
+
+#[tauri::command]
+fn open_file_with_application(file_path: String, application_path: String) -> Result<(), String> {
+  open_file_with_application_inner(file_path, application_path)
+}
+
+#[tauri::command]
+fn content_type_for_path(file_path: String) -> Result<String, String> {
+  content_type_for_path_inner(file_path)
+}
+
+#[tauri::command]
+fn set_content_type_mapping(extension: String, uti: String) -> Result<(), String> {
+  set_content_type_mapping_inner(extension, uti)
+}
+
+#[tauri::command]
+fn reveal_in_finder(path: String) -> Result<(), String> {
+  reveal_in_finder_inner(path)
+}
+
+#[tauri::command]
+fn get_app_icon(application_path: String, size: u32) -> Result<String, String> {
+  get_app_icon_inner(application_path, size)
+}
+
+#[tauri::command]
+fn get_recent_logs(limit: usize) -> Result<Vec<LogEntry>, String> {
+  Ok(logger::recent(limit))
+}
+
+#[tauri::command]
+fn search_applications(query: String, limit: usize) -> Result<Vec<InstalledApplication>, String> {
+  search_applications_inner(query, limit)
+}
+
+#[tauri::command]
+fn add_extensions(extensions: Vec<String>) -> Result<AddExtensionsResult, String> {
+  add_extensions_inner(extensions)
+}
+
+// Emits an event when the user grants or revokes access in System Settings,
+// so the frontend doesn't have to poll check_full_disk_access.
+#[cfg(target_os = "macos")]
+fn watch_full_disk_access(app: AppHandle) {
+  let mut status = check_full_disk_access_inner().unwrap_or(FullDiskAccessStatus::Denied);
+  loop {
+    thread::sleep(FULL_DISK_ACCESS_POLL_INTERVAL);
+    let current = check_full_disk_access_inner().unwrap_or(FullDiskAccessStatus::Denied);
+    if current != status {
+      status = current;
+      let _ = app.emit(FULL_DISK_ACCESS_CHANGED_EVENT, status);
+    }
+  }
+}
+
+// Emits an event when another app (e.g. Finder's Get Info) changes a default, so the list
+// doesn't go stale. Polling the path also covers the plist being replaced atomically.
+#[cfg(target_os = "macos")]
+fn watch_launch_services(app: AppHandle) {
+  let mut modified = launch_services_modified_inner();
+  let mut pending = false;
+  loop {
+    thread::sleep(ASSOCIATIONS_POLL_INTERVAL);
+    let current = launch_services_modified_inner();
+    if current != modified {
+      modified = current;
+      pending = true;
+    }
+    // cfprefsd may rewrite the file shortly after our own write, so hold a change until the
+    // grace window has passed and then compare contents; outside edits made inside the window
+    // are still reported once it ends.
+    if !pending || is_within_own_write_grace_inner() {
+      continue;
+    }
+    pending = false;
+    if !is_own_launch_services_write_inner() {
+      let _ = app.emit(ASSOCIATIONS_CHANGED_EVENT, ());
+    }
+  }
+}
+
+#[tauri::command]
+fn remove_extension_and_handler(
+  extension: String,
+  clear_handler: bool,
+) -> Result<RemoveExtensionResult, String> {
+  remove_extension_and_handler_inner(extension, clear_handler)
+}
+
+#[tauri::command]
+fn inspect_file(file_path: String) -> Result<FileInspection, String> {
+  inspect_file_inner(file_path)
+}
+
+#[tauri::command]
+fn get_change_history(limit: usize) -> Result<Vec<ChangeRecord>, String> {
+  get_change_history_inner(limit)
+}
+
+#[tauri::command]
+fn preview_set_default_application(
+  extension: String,
+  application_path: String,
+  force: Option<bool>,
+  role: Option<HandlerRole>,
+) -> Result<String, String> {
+  preview_set_default_application_inner(
+    extension,
+    application_path,
+    force.unwrap_or(false),
+    role.unwrap_or_default(),
+  )
+}
+
+#[tauri::command]
+fn undo_last_change() -> Result<FileAssociation, String> {
+  undo_last_change_inner()
+}
+
+#[tauri::command]
+fn check_duti() -> Result<DutiStatus, String> {
+  check_duti_inner()
+}
+
+#[tauri::command]
+fn get_content_type_for_extension(extension: String) -> Result<ContentTypeInfo, String> {
+  get_content_type_for_extension_inner(extension)
+}
+
+#[tauri::command]
+fn list_tracked_extensions() -> Result<Vec<String>, String> {
+  list_tracked_extensions_inner()
+}
+
+#[tauri::command]
+fn set_default_application_for_content_type(
+  content_type: String,
+  application_path: String,
+) -> Result<Vec<String>, String> {
+  set_default_application_for_content_type_inner(content_type, application_path)
+}
+
+#[tauri::command]
+fn list_raw_handlers() -> Result<Vec<RawHandlerEntry>, String> {
+  list_raw_handlers_inner()
+}
+
+#[tauri::command]
+fn delete_handler_entry(
+  selector: HandlerSelector,
+  force: bool,
+) -> Result<Vec<RawHandlerEntry>, String> {
+  delete_handler_entry_inner(selector, force)
+}
+
+#[tauri::command]
+fn detect_conflicts() -> Result<Vec<Conflict>, String> {
+  detect_conflicts_inner()
+}
+
+#[tauri::command]
+fn validate_application(
+  application_path: String,
+  extension: String,
+) -> Result<ApplicationVerdict, String> {
+  validate_application_inner(application_path, extension)
+}
+
+#[tauri::command]
+fn check_app_supports_extension(
+  application_path: String,
+  extension: String,
+) -> Result<bool, String> {
+  check_app_supports_extension_inner(application_path, extension)
+}
+
+#[tauri::command]
+fn set_default_application_for_category(
+  category: String,
+  application_path: String,
+) -> Result<BatchSetResult, String> {
+  set_default_application_for_category_inner(category, application_path)
+}
+
+#[tauri::command]
+fn pin_extension(extension: String) -> Result<Vec<String>, String> {
+  pin_extension_inner(extension)
+}
+
+#[tauri::command]
+fn unpin_extension(extension: String) -> Result<Vec<String>, String> {
+  unpin_extension_inner(extension)
+}
+
+#[tauri::command]
+fn list_pinned_extensions() -> Result<Vec<String>, String> {
+  list_pinned_extensions_inner()
+}
+
+#[tauri::command]
+fn list_all_handlers_for_extension(extension: String) -> Result<Vec<CandidateApplication>, String> {
+  list_all_handlers_for_extension_inner(extension)
+}
+
+#[tauri::command]
+fn set_extension_note(extension: String, note: String) -> Result<(), String> {
+  set_extension_note_inner(extension, note)
+}
+
+#[tauri::command]
+fn set_pinned_extensions(order: Vec<String>) -> Result<Vec<String>, String> {
+  set_pinned_extensions_inner(order)
+}
+
+#[tauri::command]
+fn verify_association(extension: String) -> Result<AssociationVerification, String> {
+  verify_association_inner(extension)
+}
+
+#[tauri::command]
+fn export_duti_config(path: String) -> Result<usize, String> {
+  export_duti_config_inner(path)
+}
+
+#[tauri::command]
+fn import_duti_config(path: String, dry_run: bool) -> Result<ImportReport, String> {
+  import_duti_config_inner(path, dry_run)
+}
+
+#[tauri::command]
+fn set_default_application_by_bundle_id(
+  extension: String,
+  bundle_id: String,
+  allow_missing: Option<bool>,
+) -> Result<FileAssociation, String> {
+  set_default_application_by_bundle_id_inner(extension, bundle_id, allow_missing.unwrap_or(false))
+}
+
+#[tauri::command]
+fn apply_profile(
+  profile_name: String,
+  app_overrides: HashMap<String, String>,
+) -> Result<BatchSetResult, String> {
+  apply_profile_inner(profile_name, app_overrides)
+}
+
+#[tauri::command]
+fn save_profile(name: String) -> Result<SavedProfile, String> {
+  save_profile_inner(name)
+}
+
+#[tauri::command]
+fn list_profiles() -> Result<Vec<SavedProfile>, String> {
+  list_profiles_inner()
+}
+
+#[tauri::command]
+fn apply_saved_profile(name: String) -> Result<ImportReport, String> {
+  apply_saved_profile_inner(name)
+}
+
+#[tauri::command]
+fn delete_profile(name: String) -> Result<Vec<SavedProfile>, String> {
+  delete_profile_inner(name)
+}
+
+#[tauri::command]
+fn reset_all_to_system_defaults(clear_extensions: bool) -> Result<Vec<FileAssociation>, String> {
+  reset_all_to_system_defaults_inner(clear_extensions)
+}
+
+#[tauri::command]
+fn set_search_roots(paths: Vec<String>) -> Result<Vec<String>, String> {
+  set_search_roots_inner(paths)
+}
+
+#[tauri::command]
+fn list_search_roots() -> Result<Vec<String>, String> {
+  list_search_roots_inner()
+}
+
+#[tauri::command]
+fn check_full_disk_access_detailed() -> Result<FullDiskAccessReport, String> {
+  check_full_disk_access_detailed_inner()
+}
+
+#[tauri::command]
+fn diagnose_extension(extension: String) -> Result<ExtensionDiagnosis, String> {
+  diagnose_extension_inner(extension)
 }
 
 fn main() {
@@ -116,8 +1041,79 @@ fn main() {
       check_full_disk_access,
       open_full_disk_access_settings,
       list_file_associations,
+      list_file_associations_streaming,
+      list_file_associations_grouped,
+      get_association_for_extension,
+      list_handlers_for_extension,
+      list_installed_applications,
       add_extension,
-      set_default_application_for_extension
+      remove_extension,
+      set_default_application_for_extension,
+      reset_extension_to_system_default,
+      list_url_scheme_handlers,
+      set_default_application_for_url_scheme,
+      list_candidate_apps_for_extension,
+      set_default_application_for_extensions,
+      export_associations,
+      import_associations,
+      list_launch_services_backups,
+      restore_launch_services_backup,
+      export_associations_to_file,
+      import_associations_from_file,
+      list_file_associations_with_icons,
+      backup_launch_services,
+      clear_bundle_cache,
+      get_bundle_info,
+      get_default_browser,
+      set_default_browser,
+      get_default_mail_client,
+      set_default_mail_client,
+      open_with,
+      rebuild_launch_services,
+      open_file_with_application,
+      content_type_for_path,
+      set_content_type_mapping,
+      reveal_in_finder,
+      get_app_icon,
+      get_recent_logs,
+      search_applications,
+      add_extensions,
+      remove_extension_and_handler,
+      inspect_file,
+      get_change_history,
+      preview_set_default_application,
+      undo_last_change,
+      check_duti,
+      get_content_type_for_extension,
+      list_tracked_extensions,
+      set_default_application_for_content_type,
+      list_raw_handlers,
+      delete_handler_entry,
+      detect_conflicts,
+      validate_application,
+      check_app_supports_extension,
+      set_default_application_for_category,
+      pin_extension,
+      unpin_extension,
+      list_pinned_extensions,
+      list_all_handlers_for_extension,
+      set_extension_note,
+      set_pinned_extensions,
+      verify_association,
+      export_duti_config,
+      import_duti_config,
+      set_default_application_by_bundle_id,
+      list_builtin_profiles,
+      apply_profile,
+      save_profile,
+      list_profiles,
+      apply_saved_profile,
+      delete_profile,
+      reset_all_to_system_defaults,
+      set_search_roots,
+      list_search_roots,
+      check_full_disk_access_detailed,
+      diagnose_extension
     ])
     .setup(|app| {
       #[cfg(target_os = "macos")]
@@ -125,7 +1121,15 @@ fn main() {
         if let Some(window) = app.get_webview_window("main") {
           let _ = window.set_focus();
         }
+
+        let handle = app.handle().clone();
+        thread::spawn(move || watch_full_disk_access(handle));
+
+        let handle = app.handle().clone();
+        thread::spawn(move || watch_launch_services(handle));
       }
+      #[cfg(not(target_os = "macos"))]
+      let _ = app;
       Ok(())
     })
     .run(tauri::generate_context!())
@@ -1,20 +1,44 @@
-use crate::{FileAssociation, DEFAULT_EXTENSIONS};
+use crate::logger;
+use crate::{
+  builtin_profiles, category_for_extension, default_extensions, AddExtensionOutcome,
+  AddExtensionStatus, AddExtensionsResult, ApplicationVerdict, ApplyStatus, AssociationChange,
+  AssociationProgress, AssociationSource, AssociationStatus, AssociationVerification,
+  BatchSetResult, BundleInfo, CandidateApplication, ChangeRecord, Conflict, ContentTypeInfo,
+  DefaultHandlerRequest, DutiStatus, ExtensionDiagnosis, ExtensionOutcome, FileAssociation,
+  FileInspection, FullDiskAccessProbe, FullDiskAccessReport, FullDiskAccessStatus, HandlerRole,
+  HandlerSelector, ImportReport, InstalledApplication, LaunchServicesBackup, ProbeOutcome,
+  RawHandlerEntry, RemoveExtensionResult, SavedProfile, SetDefaultResult, SkippedEntry,
+  UrlSchemeAssociation, DEFAULT_URL_SCHEMES,
+};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use plist::{Dictionary, Value};
-use std::collections::BTreeSet;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::env;
 use std::ffi::{c_char, c_void, CString};
 use std::fs;
-use std::io::ErrorKind;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use url::Url;
 
 type CFTypeRef = *const c_void;
 type CFStringRef = *const c_void;
 type CFAllocatorRef = *const c_void;
+type CFArrayRef = *const c_void;
+type CFURLRef = *const c_void;
 
 const CFSTRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const CF_URL_POSIX_PATH_STYLE: isize = 0;
 
 const EXTENSION_TO_CONTENT_TYPE: &[(&str, &str)] = &[
   // Office
@@ -77,8 +101,39 @@ const EXTENSION_TO_CONTENT_TYPE: &[(&str, &str)] = &[
   ("crt", "public.certificate"),
 ];
 
+const ROOT_CONTENT_TYPES: &[&str] = &["public.item", "public.data", "public.content"];
+const MDFIND_TIMEOUT: Duration = Duration::from_secs(5);
+const LSREGISTER_PATH: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/\
+LaunchServices.framework/Support/lsregister";
+
+// 从 Finder/Dock 启动的应用不会继承 shell 的 PATH，需要额外检查 Homebrew 的安装目录
+const DUTI_SEARCH_DIRS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin"];
+const BROWSER_URL_SCHEMES: &[&str] = &["http", "https"];
+const MAIL_URL_SCHEME: &str = "mailto";
+
 const CONFIG_DIR_NAME: &str = "Default Application Manager";
 const EXTENSIONS_FILE_NAME: &str = "extensions.json";
+const URL_SCHEMES_FILE_NAME: &str = "url_schemes.json";
+const CONTENT_TYPES_FILE_NAME: &str = "content_types.json";
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const PINS_FILE_NAME: &str = "pins.json";
+const NOTES_FILE_NAME: &str = "notes.json";
+const HISTORY_FILE_NAME: &str = "history.json";
+const SEARCH_ROOTS_FILE_NAME: &str = "search_roots.json";
+const MAX_HISTORY_ENTRIES: usize = 500;
+const PROFILES_DIR_NAME: &str = "profiles";
+const EXPORT_SCHEMA_VERSION: u32 = 1;
+const BACKUPS_DIR_NAME: &str = "backups";
+const EXPORTS_DIR_NAME: &str = "exports";
+const MAX_EXPORT_SNAPSHOTS: usize = 20;
+const ICONS_DIR_NAME: &str = "icons";
+const MIN_ICON_SIZE: u32 = 16;
+const MAX_ICON_SIZE: u32 = 1024;
+const LAUNCH_SERVICES_BACKUP_PREFIX: &str = "com.apple.launchservices.secure.";
+const LAUNCH_SERVICES_BACKUP_SUFFIX: &str = ".plist.bak";
+const MAX_LAUNCH_SERVICES_BACKUPS: usize = 10;
+// 小于该值的时间戳按秒解释（约为 5138 年的秒数，远小于当前的毫秒时间戳）
+const LEGACY_BACKUP_SECONDS_LIMIT: u64 = 100_000_000_000;
 
 #[link(name = "CoreFoundation", kind = "framework")]
 extern "C" {
@@ -94,9 +149,57 @@ extern "C" {
     buffer_size: isize,
     encoding: u32,
   ) -> u8;
+  fn CFArrayGetCount(the_array: CFArrayRef) -> isize;
+  fn CFArrayGetValueAtIndex(the_array: CFArrayRef, idx: isize) -> *const c_void;
+  fn CFURLCopyFileSystemPath(an_url: CFURLRef, path_style: isize) -> CFStringRef;
   fn CFRelease(cf: CFTypeRef);
 }
 
+// 解码 icns 代价较高，按应用路径在进程生命周期内缓存转换结果
+static ICON_CACHE: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+static ICON_CONVERSION_COUNTER: AtomicUsize = AtomicUsize::new(0);
+static BUNDLE_PATH_CACHE: OnceLock<Mutex<HashMap<String, PathBuf>>> = OnceLock::new();
+static APPLICATION_INDEX: Mutex<Option<Vec<InstalledApplication>>> = Mutex::new(None);
+static PREFERRED_CONTENT_TYPES: OnceLock<Mutex<HashMap<String, Option<String>>>> =
+  OnceLock::new();
+static PINNED_EXTENSIONS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+static EXTENSION_NOTES: Mutex<Option<BTreeMap<String, String>>> = Mutex::new(None);
+static USER_CONTENT_TYPES: Mutex<Option<BTreeMap<String, String>>> = Mutex::new(None);
+// 本应用最近一次写入 LaunchServices plist 的时间和内容，监视线程据此忽略自己的修改
+static LAST_OWN_LAUNCH_SERVICES_WRITE: Mutex<Option<(SystemTime, Value)>> = Mutex::new(None);
+// 重启 cfprefsd 后它可能再次写回同一文件，这段时间内先不判断修改来源
+const OWN_WRITE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Settings {
+  skip_preferences_refresh: bool,
+  refresh_launch_services_on_mismatch: bool,
+  // 默认不收录应用包内的辅助应用；需要时可在 settings.json 中打开
+  include_nested_apps: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssociationExport {
+  #[serde(default)]
+  version: u32,
+  extensions: Vec<String>,
+  associations: Vec<ExportedAssociation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedAssociation {
+  extension: String,
+  bundle_identifier: String,
+  application_name: String,
+  application_path: String,
+  // 旧版本导出的文件没有该字段，按 All 处理
+  #[serde(default)]
+  role: HandlerRole,
+}
+
 #[derive(Debug, Error)]
 enum PlatformError {
   #[error("无法获取用户目录: {0}")]
@@ -115,9 +218,21 @@ enum PlatformError {
   Command(String),
   #[error("应用信息缺少字段: {0}")]
   MissingInfo(String),
+  #[error(".{0} 是内置文件类型，无法移除")]
+  BuiltinExtension(String),
+  #[error("{0} 没有声明支持 .{1} 文件")]
+  UnsupportedByApp(String, String),
+  #[error("找不到应用 {0}，可能已被卸载")]
+  ApplicationNotFound(String),
+  #[error("修改尚未生效: {0}")]
+  NotApplied(String),
+}
+
+pub fn check_full_disk_access_inner() -> Result<FullDiskAccessStatus, String> {
+  check_full_disk_access_detailed_inner().map(|report| report.status)
 }
 
-pub fn check_full_disk_access_inner() -> Result<bool, String> {
+pub fn check_full_disk_access_detailed_inner() -> Result<FullDiskAccessReport, String> {
   use std::fs::File;
 
   // Probe a set of known protected files. If any can be opened, FDA is granted.
@@ -126,6 +241,8 @@ pub fn check_full_disk_access_inner() -> Result<bool, String> {
   )];
 
   if let Ok(home) = env::var("HOME") {
+    // The user-level TCC database exists on every account, unlike Safari/Messages history.
+    probe_paths.push(PathBuf::from(&home).join("Library/Application Support/com.apple.TCC/TCC.db"));
     probe_paths.push(
       PathBuf::from(&home)
         .join("Library/Preferences/com.apple.LaunchServices/com.apple.launchservices.secure.plist"),
@@ -134,34 +251,38 @@ pub fn check_full_disk_access_inner() -> Result<bool, String> {
     probe_paths.push(PathBuf::from(&home).join("Library/Messages/chat.db"));
   }
 
-  let mut _saw_permission_denied = false;
+  // Every probe is tried so the report can explain which files were checked.
+  let mut probes = Vec::with_capacity(probe_paths.len());
   for path in probe_paths {
-    match File::open(&path) {
-      Ok(_) => return Ok(true),
-      Err(err) if err.kind() == ErrorKind::PermissionDenied => {
-        _saw_permission_denied = true
-      }
-      Err(err) if err.kind() == ErrorKind::NotFound => continue,
+    let outcome = match File::open(&path) {
+      Ok(_) => ProbeOutcome::Readable,
+      Err(err) if err.kind() == ErrorKind::PermissionDenied => ProbeOutcome::PermissionDenied,
+      Err(err) if err.kind() == ErrorKind::NotFound => ProbeOutcome::Absent,
       Err(err) => return Err(format!("检测权限失败: {err}")),
-    }
+    };
+    probes.push(FullDiskAccessProbe {
+      path: path.display().to_string(),
+      outcome,
+    });
   }
 
-  // If any access was denied, or no probes existed, be conservative: not granted.
-  Ok(false)
+  // No probe existed at all: we can't tell whether access was granted.
+  let has = |outcome| probes.iter().any(|probe| probe.outcome == outcome);
+  let status = if has(ProbeOutcome::Readable) {
+    FullDiskAccessStatus::Granted
+  } else if has(ProbeOutcome::PermissionDenied) {
+    FullDiskAccessStatus::Denied
+  } else {
+    FullDiskAccessStatus::Indeterminate
+  };
+  Ok(FullDiskAccessReport { status, probes })
 }
 
 pub fn open_full_disk_access_settings_inner() -> Result<(), String> {
-  Command::new("open")
-    .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles")
-    .status()
-    .map_err(|err| err.to_string())
-    .and_then(|status| {
-      if status.success() {
-        Ok(())
-      } else {
-        Err(format!("打开系统设置失败，退出状态: {status}"))
-      }
-    })
+  match open_full_disk_access_settings_impl() {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
 pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
@@ -171,6 +292,52 @@ pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
   }
 }
 
+pub fn list_file_associations_streaming_inner(
+  on_resolved: impl FnMut(AssociationProgress),
+) -> Result<usize, String> {
+  match list_file_associations_streaming_impl(on_resolved) {
+    Ok(total) => Ok(total),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_file_associations_with_icons_inner() -> Result<Vec<FileAssociation>, String> {
+  match list_file_associations_with_icons_impl() {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn get_association_for_extension_inner(extension: String) -> Result<FileAssociation, String> {
+  match get_association_for_extension_impl(extension) {
+    Ok(association) => Ok(association),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_handlers_for_extension_inner(extension: String) -> Result<Vec<FileAssociation>, String> {
+  match list_handlers_for_extension_impl(extension) {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_installed_applications_inner() -> Result<Vec<InstalledApplication>, String> {
+  match list_installed_applications_impl() {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_candidate_apps_for_extension_inner(
+  extension: String,
+) -> Result<Vec<CandidateApplication>, String> {
+  match list_candidate_apps_for_extension_impl(extension) {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
 pub fn add_extension_inner(extension: String) -> Result<Vec<FileAssociation>, String> {
   match add_extension_impl(extension) {
     Ok(list) => Ok(list),
@@ -178,492 +345,4279 @@ pub fn add_extension_inner(extension: String) -> Result<Vec<FileAssociation>, St
   }
 }
 
-pub fn set_default_application_for_extension_inner(
+pub fn add_extensions_inner(extensions: Vec<String>) -> Result<AddExtensionsResult, String> {
+  match add_extensions_impl(extensions) {
+    Ok(result) => Ok(result),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn remove_extension_and_handler_inner(
   extension: String,
-  application_path: String,
-) -> Result<(), String> {
-  match set_default_application_impl(extension, application_path) {
-    Ok(()) => Ok(()),
+  clear_handler: bool,
+) -> Result<RemoveExtensionResult, String> {
+  match remove_extension_and_handler_impl(extension, clear_handler) {
+    Ok(result) => Ok(result),
     Err(err) => Err(err.to_string()),
   }
 }
 
-fn launch_services_plist_path() -> Result<PathBuf, PlatformError> {
-  let home = env::var("HOME")?;
-  Ok(PathBuf::from(home)
-    .join("Library/Preferences/com.apple.LaunchServices/com.apple.launchservices.secure.plist"))
+pub fn reset_extension_to_system_default_inner(extension: String) -> Result<FileAssociation, String> {
+  match reset_extension_to_system_default_impl(extension) {
+    Ok(association) => Ok(association),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn extensions_config_path() -> Result<PathBuf, PlatformError> {
-  let home = env::var("HOME")?;
-  Ok(
-    PathBuf::from(&home)
-      .join("Library")
-      .join("Application Support")
-      .join(CONFIG_DIR_NAME)
-      .join(EXTENSIONS_FILE_NAME),
-  )
+pub fn reset_all_to_system_defaults_inner(
+  clear_extensions: bool,
+) -> Result<Vec<FileAssociation>, String> {
+  match reset_all_to_system_defaults_impl(clear_extensions) {
+    Ok(associations) => Ok(associations),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn load_extension_list() -> Result<Vec<String>, PlatformError> {
-  let mut set: BTreeSet<String> = DEFAULT_EXTENSIONS
-    .iter()
-    .map(|ext| ensure_extension_normalized(ext))
-    .collect();
+pub fn remove_extension_inner(extension: String) -> Result<Vec<FileAssociation>, String> {
+  match remove_extension_impl(extension) {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-  let path = extensions_config_path()?;
-  if path.exists() {
-    let text = fs::read_to_string(&path)?;
-    let stored: Vec<String> =
-      serde_json::from_str(&text).map_err(|err| PlatformError::Config(err.to_string()))?;
-    for item in stored {
-      let normalized = ensure_extension_normalized(&item);
-      if !normalized.is_empty() {
-        set.insert(normalized);
-      }
-    }
+pub fn set_default_application_for_extensions_inner(
+  extensions: Vec<String>,
+  application_path: String,
+) -> Result<BatchSetResult, String> {
+  match set_default_application_for_extensions_impl(extensions, application_path) {
+    Ok(result) => Ok(result),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  Ok(set.into_iter().collect())
+pub fn export_associations_inner() -> Result<String, String> {
+  match export_associations_impl() {
+    Ok(payload) => Ok(payload),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn save_extension_list(extensions: &[String]) -> Result<(), PlatformError> {
-  let path = extensions_config_path()?;
-  if let Some(dir) = path.parent() {
-    fs::create_dir_all(dir)?;
+pub fn export_duti_config_inner(path: String) -> Result<usize, String> {
+  match export_duti_config_impl(path) {
+    Ok(count) => Ok(count),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  let payload =
-    serde_json::to_string_pretty(extensions).map_err(|err| PlatformError::Config(err.to_string()))?;
-  fs::write(&path, payload)?;
-  Ok(())
+pub fn import_duti_config_inner(path: String, dry_run: bool) -> Result<ImportReport, String> {
+  match import_duti_config_impl(path, dry_run) {
+    Ok(report) => Ok(report),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn register_extension_if_needed(extension: &str) -> Result<(), PlatformError> {
-  let mut set: BTreeSet<String> = load_extension_list()?.into_iter().collect();
-  if set.insert(extension.to_string()) {
-    let list: Vec<String> = set.into_iter().collect();
-    save_extension_list(&list)?;
+pub fn export_associations_to_file_inner(path: String) -> Result<usize, String> {
+  match export_associations_to_file_impl(path) {
+    Ok(count) => Ok(count),
+    Err(err) => Err(err.to_string()),
   }
-  Ok(())
 }
 
-fn load_launch_services_value() -> Result<Value, PlatformError> {
-  let path = launch_services_plist_path()?;
-  let mut value = if path.exists() {
-    Value::from_file(&path)?
-  } else {
-    Value::Dictionary(Dictionary::new())
-  };
+pub fn import_associations_inner(json: String, dry_run: bool) -> Result<ImportReport, String> {
+  match import_associations_impl(json, dry_run) {
+    Ok(report) => Ok(report),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-  if let Some(dict) = value.as_dictionary_mut() {
-    if !dict.contains_key("LSHandlers") {
-      dict.insert("LSHandlers".to_string(), Value::Array(Vec::new()));
-    }
+pub fn import_associations_from_file_inner(
+  path: String,
+  dry_run: bool,
+) -> Result<ImportReport, String> {
+  match import_associations_from_file_impl(path, dry_run) {
+    Ok(report) => Ok(report),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  Ok(value)
+pub fn launch_services_modified_inner() -> Option<SystemTime> {
+  let path = launch_services_plist_path().ok()?;
+  fs::metadata(path).ok()?.modified().ok()
 }
 
-fn handlers_from_value(value: &Value) -> Result<&Vec<Value>, PlatformError> {
-  value
-    .as_dictionary()
-    .and_then(|dict| dict.get("LSHandlers"))
-    .and_then(Value::as_array)
-    .ok_or(PlatformError::MissingHandlers)
+pub fn is_within_own_write_grace_inner() -> bool {
+  let Ok(last) = LAST_OWN_LAUNCH_SERVICES_WRITE.lock() else {
+    return false;
+  };
+  last.as_ref().is_some_and(|(written, _)| {
+    written
+      .elapsed()
+      .is_ok_and(|elapsed| elapsed <= OWN_WRITE_GRACE_PERIOD)
+  })
 }
 
-fn handlers_from_value_mut(value: &mut Value) -> Result<&mut Vec<Value>, PlatformError> {
-  let dict = value
-    .as_dictionary_mut()
-    .ok_or(PlatformError::MissingHandlers)?;
+pub fn is_own_launch_services_write_inner() -> bool {
+  // 按内容而不是修改时间判断：文件仍与本应用写入的一致才算自己的修改，
+  // 宽限期内其他应用的修改也能在之后被发现
+  let Ok(path) = launch_services_plist_path() else {
+    return false;
+  };
+  let Ok(current) = Value::from_file(path) else {
+    return false;
+  };
+  let Ok(last) = LAST_OWN_LAUNCH_SERVICES_WRITE.lock() else {
+    return false;
+  };
+  last.as_ref().is_some_and(|(_, written)| *written == current)
+}
 
-  if !dict.contains_key("LSHandlers") {
-    dict.insert("LSHandlers".into(), Value::Array(Vec::new()));
+pub fn set_search_roots_inner(paths: Vec<String>) -> Result<Vec<String>, String> {
+  match set_search_roots_impl(paths) {
+    Ok(roots) => Ok(roots),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  dict
-    .get_mut("LSHandlers")
-    .and_then(Value::as_array_mut)
-    .ok_or(PlatformError::MissingHandlers)
+pub fn list_search_roots_inner() -> Result<Vec<String>, String> {
+  match load_search_roots() {
+    Ok(roots) => Ok(roots.iter().map(|root| root.display().to_string()).collect()),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn find_bundle_id_for_extension(handlers: &[Value], extension: &str) -> Option<String> {
-  let normalized = extension.to_lowercase();
-  let content_type = extension_to_content_type(&normalized).map(str::to_string);
+pub fn clear_bundle_cache_inner() -> Result<(), String> {
+  clear_bundle_cache_impl();
+  Ok(())
+}
 
-  handlers.iter().find_map(|item| {
-    let dict = item.as_dictionary()?;
-    let tag = dict
-      .get("LSHandlerContentTag")
-      .and_then(Value::as_string)
-      .map(str::to_lowercase);
+pub fn backup_launch_services_inner() -> Result<String, String> {
+  match backup_launch_services_impl() {
+    Ok(path) => Ok(path),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-    let tag_class = dict
-      .get("LSHandlerContentTagClass")
-      .and_then(Value::as_string);
+pub fn list_launch_services_backups_inner() -> Result<Vec<LaunchServicesBackup>, String> {
+  match list_launch_services_backups_impl() {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-    let matches_extension =
-      tag.as_deref() == Some(normalized.as_str()) && tag_class == Some("public.filename-extension");
-    let matches_content_type = content_type.as_ref().and_then(|expected| {
-      dict
-        .get("LSHandlerContentType")
-        .and_then(Value::as_string)
-        .filter(|value| value == expected)
-    }).is_some();
+pub fn restore_launch_services_backup_inner(
+  backup_name: String,
+) -> Result<Vec<FileAssociation>, String> {
+  match restore_launch_services_backup_impl(backup_name) {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-    if matches_extension || matches_content_type {
-      dict
-        .get("LSHandlerRoleAll")
-        .and_then(Value::as_string)
-        .map(|s| s.to_string())
-        .or_else(|| {
-          dict
-            .get("LSHandlerRoleViewer")
-            .and_then(Value::as_string)
-            .map(|s| s.to_string())
-        })
-    } else {
-      None
-    }
-  })
+pub fn get_bundle_info_inner(application_path: String) -> Result<BundleInfo, String> {
+  match get_bundle_info_impl(application_path) {
+    Ok(info) => Ok(info),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn bundle_path_from_id(bundle_id: &str) -> Result<PathBuf, PlatformError> {
-  // Avoid AppleScript automation prompts; use Spotlight index via mdfind
-  // Query Spotlight for exact bundle identifier
-  let query = format!("kMDItemCFBundleIdentifier == '{}'", bundle_id);
-  let output = Command::new("mdfind").arg(query).output().map_err(PlatformError::Io)?;
-  if output.status.success() {
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let candidates: Vec<PathBuf> = stdout
-      .lines()
-      .filter(|line| line.trim().ends_with(".app"))
-      .map(|line| PathBuf::from(line.trim()))
-      .collect();
+pub fn list_url_scheme_handlers_inner() -> Result<Vec<UrlSchemeAssociation>, String> {
+  match list_url_scheme_handlers_impl() {
+    Ok(list) => Ok(list),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-    // Verify candidates’ Info.plist identifier and prefer common application locations
-    let preferred_prefixes = vec![
-      PathBuf::from("/Applications"),
-      PathBuf::from("/System/Applications"),
-      PathBuf::from("/System/Applications/Utilities"),
-      PathBuf::from(format!("{}/Applications", env::var("HOME").unwrap_or_default())),
-    ];
+pub fn get_default_browser_inner() -> Result<UrlSchemeAssociation, String> {
+  Ok(resolve_url_scheme_association(BROWSER_URL_SCHEMES[0]))
+}
 
-    // First, try to match exact bundle id by reading Info.plist
-    for p in &candidates {
-      let info_path = p.join("Contents").join("Info.plist");
-      if let Ok(value) = Value::from_file(&info_path) {
-        if let Some(dict) = value.as_dictionary() {
-          let id = dict.get("CFBundleIdentifier").and_then(Value::as_string);
-          if id.map(|i| i.eq_ignore_ascii_case(bundle_id)).unwrap_or(false) {
-            return Ok(p.clone());
-          }
-        }
-      }
-    }
+pub fn set_default_browser_inner(
+  application_path: String,
+) -> Result<DefaultHandlerRequest, String> {
+  match set_default_browser_impl(application_path) {
+    Ok(request) => Ok(request),
+    Err(err) => Err(err.to_string()),
+  }
+}
 
-    // Next, prefer by location
-    if let Some(best) = candidates
-      .iter()
-      .find(|p| preferred_prefixes.iter().any(|pref| p.starts_with(pref)))
-    {
-      return Ok(best.clone());
-    }
+pub fn get_default_mail_client_inner() -> Result<UrlSchemeAssociation, String> {
+  Ok(resolve_url_scheme_association(MAIL_URL_SCHEME))
+}
 
-    // Finally, take the first result
-    if let Some(first) = candidates.into_iter().next() {
-      return Ok(first);
-    }
+pub fn set_default_mail_client_inner(
+  application_path: String,
+) -> Result<DefaultHandlerRequest, String> {
+  match set_default_mail_client_impl(application_path) {
+    Ok(request) => Ok(request),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  // Fallback: scan common application folders and match Info.plist CFBundleIdentifier
-  if let Some(found) = find_app_in_common_locations(bundle_id) {
-    return Ok(found);
+pub fn set_default_application_for_url_scheme_inner(
+  scheme: String,
+  application_path: String,
+) -> Result<(), String> {
+  match set_default_application_for_url_scheme_impl(scheme, application_path) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
   }
+}
 
-  Err(PlatformError::Command("未找到应用路径".into()))
+pub fn rebuild_launch_services_inner(
+  deep: bool,
+  on_progress: impl FnMut(&str),
+) -> Result<u64, String> {
+  match rebuild_launch_services_impl(deep, on_progress) {
+    Ok(duration) => Ok(duration),
+    Err(err) => Err(err.to_string()),
+  }
 }
 
-fn find_app_in_common_locations(bundle_id: &str) -> Option<PathBuf> {
-  let mut roots = vec![
-    PathBuf::from("/Applications"),
+pub fn open_with_inner(file_path: String, application_path: String) -> Result<(), String> {
+  match open_with_impl(file_path, application_path) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn open_file_with_application_inner(
+  file_path: String,
+  application_path: String,
+) -> Result<(), String> {
+  match open_file_with_application_impl(file_path, application_path) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn content_type_for_path_inner(file_path: String) -> Result<String, String> {
+  match content_type_for_path_impl(file_path) {
+    Ok(content_type) => Ok(content_type),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_content_type_mapping_inner(extension: String, uti: String) -> Result<(), String> {
+  match set_content_type_mapping_impl(extension, uti) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn reveal_in_finder_inner(path: String) -> Result<(), String> {
+  match reveal_in_finder_impl(path) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn get_app_icon_inner(application_path: String, size: u32) -> Result<String, String> {
+  match get_app_icon_impl(application_path, size) {
+    Ok(icon) => Ok(icon),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn search_applications_inner(
+  query: String,
+  limit: usize,
+) -> Result<Vec<InstalledApplication>, String> {
+  Ok(search_applications_impl(&query, limit))
+}
+
+pub fn inspect_file_inner(file_path: String) -> Result<FileInspection, String> {
+  match inspect_file_impl(file_path) {
+    Ok(association) => Ok(association),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_default_application_for_extension_inner(
+  extension: String,
+  application_path: String,
+  force: bool,
+  role: HandlerRole,
+) -> Result<SetDefaultResult, String> {
+  match set_default_application_impl(extension, application_path, force, role) {
+    Ok(result) => Ok(result),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn preview_set_default_application_inner(
+  extension: String,
+  application_path: String,
+  force: bool,
+  role: HandlerRole,
+) -> Result<String, String> {
+  match preview_set_default_application_impl(extension, application_path, force, role) {
+    Ok(diff) => Ok(diff),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn undo_last_change_inner() -> Result<FileAssociation, String> {
+  match undo_last_change_impl() {
+    Ok(association) => Ok(association),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn get_change_history_inner(limit: usize) -> Result<Vec<ChangeRecord>, String> {
+  match get_change_history_impl(limit) {
+    Ok(records) => Ok(records),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn check_duti_inner() -> Result<DutiStatus, String> {
+  Ok(check_duti_impl())
+}
+
+pub fn get_content_type_for_extension_inner(extension: String) -> Result<ContentTypeInfo, String> {
+  match get_content_type_for_extension_impl(extension) {
+    Ok(info) => Ok(info),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_tracked_extensions_inner() -> Result<Vec<String>, String> {
+  // 只读取 extensions.json，不解析处理程序，供界面快速渲染扩展名列表
+  match load_extension_list() {
+    Ok(extensions) => Ok(extensions),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn save_profile_inner(name: String) -> Result<SavedProfile, String> {
+  match save_profile_impl(name) {
+    Ok(profile) => Ok(profile),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_profiles_inner() -> Result<Vec<SavedProfile>, String> {
+  match list_profiles_impl() {
+    Ok(profiles) => Ok(profiles),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn apply_saved_profile_inner(name: String) -> Result<ImportReport, String> {
+  match apply_saved_profile_impl(name) {
+    Ok(report) => Ok(report),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn delete_profile_inner(name: String) -> Result<Vec<SavedProfile>, String> {
+  match delete_profile_impl(name) {
+    Ok(profiles) => Ok(profiles),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn apply_profile_inner(
+  profile_name: String,
+  app_overrides: HashMap<String, String>,
+) -> Result<BatchSetResult, String> {
+  match apply_profile_impl(profile_name, app_overrides) {
+    Ok(result) => Ok(result),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_default_application_by_bundle_id_inner(
+  extension: String,
+  bundle_id: String,
+  allow_missing: bool,
+) -> Result<FileAssociation, String> {
+  match set_default_application_by_bundle_id_impl(extension, bundle_id, allow_missing) {
+    Ok(association) => Ok(association),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_default_application_for_content_type_inner(
+  content_type: String,
+  application_path: String,
+) -> Result<Vec<String>, String> {
+  match set_default_application_for_content_type_impl(content_type, application_path) {
+    Ok(extensions) => Ok(extensions),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_raw_handlers_inner() -> Result<Vec<RawHandlerEntry>, String> {
+  match list_raw_handlers_impl() {
+    Ok(entries) => Ok(entries),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn delete_handler_entry_inner(
+  selector: HandlerSelector,
+  force: bool,
+) -> Result<Vec<RawHandlerEntry>, String> {
+  match delete_handler_entry_impl(selector, force) {
+    Ok(entries) => Ok(entries),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn detect_conflicts_inner() -> Result<Vec<Conflict>, String> {
+  match detect_conflicts_impl() {
+    Ok(conflicts) => Ok(conflicts),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn validate_application_inner(
+  application_path: String,
+  extension: String,
+) -> Result<ApplicationVerdict, String> {
+  match validate_application_impl(application_path, extension) {
+    Ok(verdict) => Ok(verdict),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn check_app_supports_extension_inner(
+  application_path: String,
+  extension: String,
+) -> Result<bool, String> {
+  match check_app_supports_extension_impl(application_path, extension) {
+    Ok(supported) => Ok(supported),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_default_application_for_category_inner(
+  category: String,
+  application_path: String,
+) -> Result<BatchSetResult, String> {
+  match set_default_application_for_category_impl(category, application_path) {
+    Ok(result) => Ok(result),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn pin_extension_inner(extension: String) -> Result<Vec<String>, String> {
+  match update_pinned_extension(extension, true) {
+    Ok(pins) => Ok(pins),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn unpin_extension_inner(extension: String) -> Result<Vec<String>, String> {
+  match update_pinned_extension(extension, false) {
+    Ok(pins) => Ok(pins),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn diagnose_extension_inner(extension: String) -> Result<ExtensionDiagnosis, String> {
+  Ok(diagnose_extension_impl(extension))
+}
+
+pub fn verify_association_inner(extension: String) -> Result<AssociationVerification, String> {
+  match verify_association_impl(extension) {
+    Ok(verification) => Ok(verification),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_pinned_extensions_inner(order: Vec<String>) -> Result<Vec<String>, String> {
+  match set_pinned_extensions_impl(order) {
+    Ok(pins) => Ok(pins),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_pinned_extensions_inner() -> Result<Vec<String>, String> {
+  match pinned_extensions() {
+    Ok(pins) => Ok(pins),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn list_all_handlers_for_extension_inner(
+  extension: String,
+) -> Result<Vec<CandidateApplication>, String> {
+  match list_all_handlers_for_extension_impl(extension) {
+    Ok(candidates) => Ok(candidates),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+pub fn set_extension_note_inner(extension: String, note: String) -> Result<(), String> {
+  match set_extension_note_impl(extension, note) {
+    Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
+}
+
+// 各版本系统设置的锚点名称不同，直达“完全磁盘访问”失败时退回到“隐私与安全性”页面
+const FULL_DISK_ACCESS_SETTINGS_URLS: &[&str] = &[
+  "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles",
+  "x-apple.systempreferences:com.apple.preference.security",
+];
+
+fn open_full_disk_access_settings_impl() -> Result<(), PlatformError> {
+  let mut failures = Vec::new();
+  for url in FULL_DISK_ACCESS_SETTINGS_URLS {
+    let output = Command::new("open").arg(url).output()?;
+    if output.status.success() {
+      return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    logger::warn(format!("打开 {url} 失败: {} {stderr}", output.status));
+    failures.push(format!("{url}（{}）: {stderr}", output.status));
+  }
+
+  Err(PlatformError::Command(format!(
+    "打开系统设置失败: {}",
+    failures.join("；")
+  )))
+}
+
+fn launch_services_plist_path() -> Result<PathBuf, PlatformError> {
+  let home = env::var("HOME")?;
+  Ok(PathBuf::from(home)
+    .join("Library/Preferences/com.apple.LaunchServices/com.apple.launchservices.secure.plist"))
+}
+
+fn config_dir_path() -> Result<PathBuf, PlatformError> {
+  let home = env::var("HOME")?;
+  Ok(
+    PathBuf::from(&home)
+      .join("Library")
+      .join("Application Support")
+      .join(CONFIG_DIR_NAME),
+  )
+}
+
+fn extensions_config_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(EXTENSIONS_FILE_NAME))
+}
+
+fn url_schemes_config_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(URL_SCHEMES_FILE_NAME))
+}
+
+fn content_types_config_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(CONTENT_TYPES_FILE_NAME))
+}
+
+fn settings_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(SETTINGS_FILE_NAME))
+}
+
+fn search_roots_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(SEARCH_ROOTS_FILE_NAME))
+}
+
+fn pins_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(PINS_FILE_NAME))
+}
+
+fn notes_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(NOTES_FILE_NAME))
+}
+
+fn history_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(HISTORY_FILE_NAME))
+}
+
+fn icons_dir_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(ICONS_DIR_NAME))
+}
+
+fn profiles_dir_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(PROFILES_DIR_NAME))
+}
+
+fn backups_dir_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(BACKUPS_DIR_NAME))
+}
+
+fn exports_dir_path() -> Result<PathBuf, PlatformError> {
+  Ok(config_dir_path()?.join(EXPORTS_DIR_NAME))
+}
+
+fn unix_timestamp() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default()
+}
+
+fn unix_timestamp_millis() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or_default()
+}
+
+// 文件不存在时返回默认值；内容无法解析时报错，避免随后的写入把用户原有的文件覆盖掉
+fn read_json_store<T: DeserializeOwned + Default>(path: &Path) -> Result<T, PlatformError> {
+  if !path.exists() {
+    return Ok(T::default());
+  }
+  let text = fs::read_to_string(path)?;
+  serde_json::from_str(&text)
+    .map_err(|err| PlatformError::Config(format!("{} 格式无效: {err}", path.display())))
+}
+
+fn write_json_store<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), PlatformError> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  let payload =
+    serde_json::to_string_pretty(value).map_err(|err| PlatformError::Config(err.to_string()))?;
+  fs::write(path, payload)?;
+  Ok(())
+}
+
+// 首次使用时从文件加载，读取失败不会缓存，下次调用会重新尝试
+fn load_cached<T: Clone>(
+  cache: &Mutex<Option<T>>,
+  load: impl FnOnce() -> Result<T, PlatformError>,
+) -> Result<T, PlatformError> {
+  let mut cache = cache
+    .lock()
+    .map_err(|_| PlatformError::Config("配置缓存不可用".into()))?;
+  if let Some(value) = cache.as_ref() {
+    return Ok(value.clone());
+  }
+  let value = load()?;
+  *cache = Some(value.clone());
+  Ok(value)
+}
+
+// 先写文件再更新缓存，写入失败时内存中的状态与磁盘保持一致
+fn store_cached<T: Serialize>(
+  cache: &Mutex<Option<T>>,
+  path: &Path,
+  value: T,
+) -> Result<(), PlatformError> {
+  write_json_store(path, &value)?;
+  if let Ok(mut cache) = cache.lock() {
+    *cache = Some(value);
+  }
+  Ok(())
+}
+
+fn load_extension_list() -> Result<Vec<String>, PlatformError> {
+  let mut set: BTreeSet<String> = default_extensions()
+    .map(ensure_extension_normalized)
+    .collect();
+
+  let stored: Vec<String> = read_json_store(&extensions_config_path()?)?;
+  for item in stored {
+    let normalized = ensure_extension_normalized(&item);
+    if !normalized.is_empty() {
+      set.insert(normalized);
+    }
+  }
+
+  Ok(set.into_iter().collect())
+}
+
+fn save_extension_list(extensions: &[String]) -> Result<(), PlatformError> {
+  write_json_store(&extensions_config_path()?, extensions)
+}
+
+fn register_extension_if_needed(extension: &str) -> Result<(), PlatformError> {
+  register_extensions_if_needed(&[extension.to_string()])
+}
+
+fn register_extensions_if_needed(extensions: &[String]) -> Result<(), PlatformError> {
+  let mut set: BTreeSet<String> = load_extension_list()?.into_iter().collect();
+  let mut changed = false;
+  for extension in extensions {
+    changed |= set.insert(extension.clone());
+  }
+  if changed {
+    let list: Vec<String> = set.into_iter().collect();
+    save_extension_list(&list)?;
+  }
+  Ok(())
+}
+
+fn unregister_extension(extension: &str) -> Result<(), PlatformError> {
+  let mut set: BTreeSet<String> = load_extension_list()?.into_iter().collect();
+  if set.remove(extension) {
+    let list: Vec<String> = set.into_iter().collect();
+    save_extension_list(&list)?;
+  }
+  Ok(())
+}
+
+fn pinned_extensions() -> Result<Vec<String>, PlatformError> {
+  load_cached(&PINNED_EXTENSIONS, load_pinned_extensions)
+}
+
+fn load_pinned_extensions() -> Result<Vec<String>, PlatformError> {
+  // 文件中的顺序就是界面上的显示顺序，去重时保留先出现的位置
+  let stored: Vec<String> = read_json_store(&pins_path()?)?;
+  let mut pins: Vec<String> = Vec::with_capacity(stored.len());
+  for ext in stored {
+    let normalized = ensure_extension_normalized(&ext);
+    if !normalized.is_empty() && !pins.contains(&normalized) {
+      pins.push(normalized);
+    }
+  }
+  Ok(pins)
+}
+
+fn save_pinned_extensions(pins: Vec<String>) -> Result<(), PlatformError> {
+  store_cached(&PINNED_EXTENSIONS, &pins_path()?, pins)
+}
+
+fn is_extension_pinned(extension: &str) -> bool {
+  pinned_extensions()
+    .map(|pins| pins.iter().any(|ext| ext == extension))
+    .unwrap_or(false)
+}
+
+fn update_pinned_extension(extension: String, pinned: bool) -> Result<Vec<String>, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  // 固定尚未加入列表的扩展名时顺便加入列表，否则界面上看不到它
+  if pinned {
+    register_extension_if_needed(&normalized)?;
+  }
+
+  let mut pins = pinned_extensions()?;
+  let changed = if pinned {
+    // 新固定的扩展名排在最后
+    let absent = !pins.contains(&normalized);
+    if absent {
+      pins.push(normalized);
+    }
+    absent
+  } else {
+    let before = pins.len();
+    pins.retain(|ext| ext != &normalized);
+    pins.len() != before
+  };
+
+  if changed {
+    save_pinned_extensions(pins.clone())?;
+  }
+  Ok(pins)
+}
+
+fn set_pinned_extensions_impl(order: Vec<String>) -> Result<Vec<String>, PlatformError> {
+  let mut normalized_order: Vec<String> = Vec::with_capacity(order.len());
+  for ext in &order {
+    let normalized = validate_extension(ext)?;
+    if !normalized_order.contains(&normalized) {
+      normalized_order.push(normalized);
+    }
+  }
+  for ext in &normalized_order {
+    register_extension_if_needed(ext)?;
+  }
+
+  save_pinned_extensions(normalized_order.clone())?;
+  Ok(normalized_order)
+}
+
+// 固定的扩展名按保存的顺序排在最前，其余保持字母序
+fn ordered_extension_list() -> Result<Vec<String>, PlatformError> {
+  let extensions = load_extension_list()?;
+  let pins = pinned_extensions()?;
+
+  let mut ordered: Vec<String> = pins
+    .iter()
+    .filter(|ext| extensions.contains(ext))
+    .cloned()
+    .collect();
+  ordered.extend(extensions.into_iter().filter(|ext| !pins.contains(ext)));
+  Ok(ordered)
+}
+
+fn extension_notes() -> Result<BTreeMap<String, String>, PlatformError> {
+  load_cached(&EXTENSION_NOTES, load_extension_notes)
+}
+
+fn load_extension_notes() -> Result<BTreeMap<String, String>, PlatformError> {
+  let stored: BTreeMap<String, String> = read_json_store(&notes_path()?)?;
+  Ok(
+    stored
+      .into_iter()
+      .map(|(ext, note)| (ensure_extension_normalized(&ext), note.trim().to_string()))
+      .filter(|(ext, note)| !ext.is_empty() && !note.is_empty())
+      .collect(),
+  )
+}
+
+fn extension_note(extension: &str) -> Option<String> {
+  extension_notes()
+    .ok()
+    .and_then(|notes| notes.get(extension).cloned())
+}
+
+fn set_extension_note_impl(extension: String, note: String) -> Result<(), PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let note = note.trim();
+
+  let mut notes = extension_notes()?;
+  // 传入空备注表示删除
+  if note.is_empty() {
+    notes.remove(&normalized);
+  } else {
+    notes.insert(normalized, note.to_string());
+  }
+
+  store_cached(&EXTENSION_NOTES, &notes_path()?, notes)
+}
+
+fn load_custom_url_schemes() -> Result<Vec<String>, PlatformError> {
+  let stored: Vec<String> = read_json_store(&url_schemes_config_path()?)?;
+  let set: BTreeSet<String> = stored
+    .iter()
+    .map(|scheme| scheme.trim().to_lowercase())
+    .filter(|scheme| !scheme.is_empty() && !DEFAULT_URL_SCHEMES.contains(&scheme.as_str()))
+    .collect();
+  Ok(set.into_iter().collect())
+}
+
+fn load_url_scheme_list() -> Result<Vec<String>, PlatformError> {
+  // 内置协议保持固定顺序，自定义协议按字母顺序排在后面
+  let mut schemes: Vec<String> = DEFAULT_URL_SCHEMES.iter().map(|s| s.to_string()).collect();
+  schemes.extend(load_custom_url_schemes()?);
+  Ok(schemes)
+}
+
+fn register_url_scheme_if_needed(scheme: &str) -> Result<(), PlatformError> {
+  if DEFAULT_URL_SCHEMES.contains(&scheme) {
+    return Ok(());
+  }
+
+  let mut schemes = load_custom_url_schemes()?;
+  if schemes.iter().any(|existing| existing == scheme) {
+    return Ok(());
+  }
+  schemes.push(scheme.to_string());
+  schemes.sort();
+
+  write_json_store(&url_schemes_config_path()?, &schemes)
+}
+
+fn set_content_type_mapping_impl(extension: String, uti: String) -> Result<(), PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let uti = uti.trim();
+  validate_content_type_chars(uti)?;
+
+  let mut overrides = user_content_types()?;
+  // 传入空的内容类型表示删除覆盖，恢复使用内置映射
+  if uti.is_empty() {
+    overrides.remove(&normalized);
+  } else {
+    overrides.insert(normalized, uti.to_string());
+  }
+
+  store_cached(&USER_CONTENT_TYPES, &content_types_config_path()?, overrides)
+}
+
+fn validate_content_type_chars(uti: &str) -> Result<(), PlatformError> {
+  if !uti
+    .chars()
+    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'))
+  {
+    return Err(PlatformError::InvalidSelection(format!(
+      "内容类型只能包含字母、数字、. 或 -: {uti}"
+    )));
+  }
+  Ok(())
+}
+
+fn load_launch_services_value() -> Result<Value, PlatformError> {
+  let path = launch_services_plist_path()?;
+  let mut value = if path.exists() {
+    Value::from_file(&path)?
+  } else {
+    Value::Dictionary(Dictionary::new())
+  };
+
+  // 结构异常时直接报错，避免重新初始化后写回把原有配置覆盖掉
+  let dict = value.as_dictionary_mut().ok_or_else(|| {
+    PlatformError::Config(format!("{} 的顶层不是字典", path.display()))
+  })?;
+  match dict.get("LSHandlers") {
+    None => {
+      dict.insert("LSHandlers".to_string(), Value::Array(Vec::new()));
+    }
+    Some(Value::Array(handlers)) => {
+      let invalid = handlers
+        .iter()
+        .filter(|handler| handler.as_dictionary().is_none())
+        .count();
+      if invalid > 0 {
+        logger::warn(format!("LSHandlers 中有 {invalid} 个条目不是字典，已原样保留"));
+      }
+    }
+    Some(_) => {
+      return Err(PlatformError::Config(format!(
+        "{} 中的 LSHandlers 不是数组",
+        path.display()
+      )));
+    }
+  }
+
+  Ok(value)
+}
+
+fn handlers_from_value(value: &Value) -> Result<&Vec<Value>, PlatformError> {
+  value
+    .as_dictionary()
+    .and_then(|dict| dict.get("LSHandlers"))
+    .and_then(Value::as_array)
+    .ok_or(PlatformError::MissingHandlers)
+}
+
+fn handlers_from_value_mut(value: &mut Value) -> Result<&mut Vec<Value>, PlatformError> {
+  let dict = value
+    .as_dictionary_mut()
+    .ok_or(PlatformError::MissingHandlers)?;
+
+  if !dict.contains_key("LSHandlers") {
+    dict.insert("LSHandlers".into(), Value::Array(Vec::new()));
+  }
+
+  dict
+    .get_mut("LSHandlers")
+    .and_then(Value::as_array_mut)
+    .ok_or(PlatformError::MissingHandlers)
+}
+
+fn list_raw_handlers_impl() -> Result<Vec<RawHandlerEntry>, PlatformError> {
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+
+  // 保留在 plist 中的原始位置，非字典条目也占一个序号，字段全部为空
+  Ok(
+    handlers
+      .iter()
+      .enumerate()
+      .map(|(index, handler)| {
+        let field = |key: &str| {
+          handler
+            .as_dictionary()
+            .and_then(|dict| dict.get(key))
+            .and_then(Value::as_string)
+            .map(str::to_string)
+        };
+        RawHandlerEntry {
+          index,
+          content_tag: field("LSHandlerContentTag"),
+          content_tag_class: field("LSHandlerContentTagClass"),
+          content_type: field("LSHandlerContentType"),
+          role_all: field("LSHandlerRoleAll"),
+          role_viewer: field("LSHandlerRoleViewer"),
+          role_editor: field("LSHandlerRoleEditor"),
+        }
+      })
+      .collect(),
+  )
+}
+
+fn delete_handler_entry_impl(
+  selector: HandlerSelector,
+  force: bool,
+) -> Result<Vec<RawHandlerEntry>, PlatformError> {
+  let key = handler_key_from_selector(&selector)?;
+
+  // 每次都重新读取 plist，避免基于过期的内容删除条目
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+  let matched = handlers
+    .iter()
+    .filter(|handler| handler_matches_key(handler, &key))
+    .count();
+
+  if matched == 0 {
+    return Err(PlatformError::InvalidSelection("没有匹配的 LSHandlers 条目".into()));
+  }
+  if matched > 1 && !force {
+    return Err(PlatformError::InvalidSelection(format!(
+      "匹配到 {matched} 个 LSHandlers 条目，请确认后强制删除"
+    )));
+  }
+
+  handlers.retain(|handler| !handler_matches_key(handler, &key));
+  save_launch_services_value(&value)?;
+  restart_preferences_daemon();
+
+  list_raw_handlers_impl()
+}
+
+enum HandlerKey {
+  Tag(String, String),
+  ContentType(String),
+}
+
+fn handler_key_from_selector(selector: &HandlerSelector) -> Result<HandlerKey, PlatformError> {
+  let trimmed = |field: &Option<String>| {
+    field
+      .as_deref()
+      .map(str::trim)
+      .filter(|text| !text.is_empty())
+      .map(str::to_string)
+  };
+
+  // 只能按内容类型，或按标签加标签类别其中一种方式定位条目
+  match (
+    trimmed(&selector.content_type),
+    trimmed(&selector.content_tag),
+    trimmed(&selector.content_tag_class),
+  ) {
+    (Some(content_type), None, None) => Ok(HandlerKey::ContentType(content_type)),
+    (None, Some(tag), Some(tag_class)) => Ok(HandlerKey::Tag(tag, tag_class)),
+    _ => Err(PlatformError::InvalidSelection(
+      "请指定内容类型，或同时指定标签和标签类别".into(),
+    )),
+  }
+}
+
+fn handler_matches_key(handler: &Value, key: &HandlerKey) -> bool {
+  let Some(dict) = handler.as_dictionary() else {
+    return false;
+  };
+  let field = |name: &str| dict.get(name).and_then(Value::as_string);
+  match key {
+    HandlerKey::Tag(tag, tag_class) => {
+      field("LSHandlerContentTag").is_some_and(|value| value.eq_ignore_ascii_case(tag))
+        && field("LSHandlerContentTagClass") == Some(tag_class.as_str())
+    }
+    HandlerKey::ContentType(content_type) => {
+      field("LSHandlerContentType") == Some(content_type.as_str())
+    }
+  }
+}
+
+fn find_bundle_id_for_extension(handlers: &[Value], extension: &str) -> Option<String> {
+  handler_entries_for_extension(handlers, extension).find_map(|dict| {
+    dict
+      .get("LSHandlerRoleAll")
+      .and_then(Value::as_string)
+      .map(|s| s.to_string())
+      .or_else(|| {
+        dict
+          .get("LSHandlerRoleViewer")
+          .and_then(Value::as_string)
+          .map(|s| s.to_string())
+      })
+  })
+}
+
+fn find_role_bundle_id_for_extension(
+  handlers: &[Value],
+  extension: &str,
+  role: HandlerRole,
+) -> Option<String> {
+  if role == HandlerRole::All {
+    return find_bundle_id_for_extension(handlers, extension);
+  }
+  handler_entries_for_extension(handlers, extension).find_map(|dict| {
+    dict
+      .get(role_plist_key(role))
+      .and_then(Value::as_string)
+      .map(|s| s.to_string())
+  })
+}
+
+fn has_handler_entry(handlers: &[Value], extension: &str) -> bool {
+  handler_entries_for_extension(handlers, extension)
+    .next()
+    .is_some()
+}
+
+fn handler_entries_for_extension<'a>(
+  handlers: &'a [Value],
+  extension: &str,
+) -> impl Iterator<Item = &'a Dictionary> {
+  let normalized = extension.to_lowercase();
+  let content_type = resolved_content_type(&normalized);
+
+  handlers.iter().filter_map(move |item| {
+    let dict = item.as_dictionary()?;
+    let tag = dict
+      .get("LSHandlerContentTag")
+      .and_then(Value::as_string)
+      .map(str::to_lowercase);
+
+    let tag_class = dict
+      .get("LSHandlerContentTagClass")
+      .and_then(Value::as_string);
+
+    let matches_extension =
+      tag.as_deref() == Some(normalized.as_str()) && tag_class == Some("public.filename-extension");
+    let matches_content_type = content_type.as_ref().and_then(|expected| {
+      dict
+        .get("LSHandlerContentType")
+        .and_then(Value::as_string)
+        .filter(|value| value == expected)
+    }).is_some();
+
+    if matches_extension || matches_content_type {
+      Some(dict)
+    } else {
+      None
+    }
+  })
+}
+
+fn current_bundle_id_for_extension(handlers: &[Value], extension: &str) -> Option<String> {
+  find_bundle_id_for_extension(handlers, extension)
+    .or_else(|| system_default_bundle_id_for_extension(extension))
+}
+
+fn bundle_path_from_id(bundle_id: &str) -> Result<PathBuf, PlatformError> {
+  // 多个扩展名常常共享同一个处理程序，缓存解析结果避免重复调用 mdfind
+  let cache = BUNDLE_PATH_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+  let key = bundle_id.to_ascii_lowercase();
+  if let Ok(mut cache) = cache.lock() {
+    match cache.get(&key) {
+      Some(path) if path.exists() => return Ok(path.clone()),
+      Some(_) => {
+        cache.remove(&key);
+      }
+      None => {}
+    }
+  }
+
+  let path = lookup_bundle_path(bundle_id)?;
+  if let Ok(mut cache) = cache.lock() {
+    cache.insert(key, path.clone());
+  }
+  Ok(path)
+}
+
+fn clear_bundle_cache_impl() {
+  if let Some(cache) = BUNDLE_PATH_CACHE.get() {
+    if let Ok(mut cache) = cache.lock() {
+      cache.clear();
+    }
+  }
+  if let Ok(mut index) = APPLICATION_INDEX.lock() {
+    *index = None;
+  }
+  if let Some(cache) = PREFERRED_CONTENT_TYPES.get() {
+    if let Ok(mut cache) = cache.lock() {
+      cache.clear();
+    }
+  }
+}
+
+fn lookup_bundle_path(bundle_id: &str) -> Result<PathBuf, PlatformError> {
+  // LaunchServices 按优先级返回已登记的应用位置，比 mdfind 更快也更准确
+  if let Some(path) = application_paths_for_bundle_id(bundle_id)
+    .into_iter()
+    .find(|path| path.exists())
+  {
+    return Ok(path);
+  }
+
+  // Avoid AppleScript automation prompts; use Spotlight index via mdfind
+  // Query Spotlight for exact bundle identifier
+  let query = format!("kMDItemCFBundleIdentifier == '{}'", bundle_id);
+  // Spotlight 被禁用或卡住时 mdfind 可能一直不返回，超时后改为扫描常见应用目录
+  let output = output_with_timeout(Command::new("mdfind").arg(query), MDFIND_TIMEOUT);
+  if let Some(output) = output.filter(|output| output.status.success()) {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let candidates: Vec<PathBuf> = stdout
+      .lines()
+      .filter(|line| line.trim().ends_with(".app"))
+      .map(|line| PathBuf::from(line.trim()))
+      .collect();
+
+    // Verify candidates’ Info.plist identifier and prefer common application locations
+    let preferred_prefixes = vec![
+      PathBuf::from("/Applications"),
+      PathBuf::from("/System/Applications"),
+      PathBuf::from("/System/Applications/Utilities"),
+      PathBuf::from(format!("{}/Applications", env::var("HOME").unwrap_or_default())),
+    ];
+
+    // First, try to match exact bundle id by reading Info.plist
+    for p in &candidates {
+      let info_path = p.join("Contents").join("Info.plist");
+      if let Ok(value) = Value::from_file(&info_path) {
+        if let Some(dict) = value.as_dictionary() {
+          let id = dict.get("CFBundleIdentifier").and_then(Value::as_string);
+          if id.map(|i| i.eq_ignore_ascii_case(bundle_id)).unwrap_or(false) {
+            return Ok(p.clone());
+          }
+        }
+      }
+    }
+
+    // Next, prefer by location
+    if let Some(best) = candidates
+      .iter()
+      .find(|p| preferred_prefixes.iter().any(|pref| p.starts_with(pref)))
+    {
+      return Ok(best.clone());
+    }
+
+    // Finally, take the first result
+    if let Some(first) = candidates.into_iter().next() {
+      return Ok(first);
+    }
+  }
+
+  // Fallback: scan common application folders and match Info.plist CFBundleIdentifier
+  if let Some(found) = find_app_in_common_locations(bundle_id) {
+    return Ok(found);
+  }
+
+  Err(PlatformError::Command("未找到应用路径".into()))
+}
+
+fn output_with_timeout(command: &mut Command, timeout: Duration) -> Option<Output> {
+  let mut child = command
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .ok()?;
+
+  // 在单独的线程中读取输出，避免管道写满后子进程阻塞
+  let mut stdout = child.stdout.take()?;
+  let mut stderr = child.stderr.take()?;
+  let stdout_reader = thread::spawn(move || {
+    let mut buffer = Vec::new();
+    let _ = stdout.read_to_end(&mut buffer);
+    buffer
+  });
+  let stderr_reader = thread::spawn(move || {
+    let mut buffer = Vec::new();
+    let _ = stderr.read_to_end(&mut buffer);
+    buffer
+  });
+
+  let deadline = Instant::now() + timeout;
+  let status = loop {
+    match child.try_wait() {
+      Ok(Some(status)) => break status,
+      Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+      _ => {
+        let _ = child.kill();
+        let _ = child.wait();
+        logger::warn(format!("命令执行超时，已终止: {:?}", command));
+        return None;
+      }
+    }
+  };
+
+  Some(Output {
+    status,
+    stdout: stdout_reader.join().unwrap_or_default(),
+    stderr: stderr_reader.join().unwrap_or_default(),
+  })
+}
+
+fn application_roots() -> Vec<PathBuf> {
+  let mut roots = vec![
+    PathBuf::from("/Applications"),
     PathBuf::from("/System/Applications"),
     PathBuf::from("/System/Applications/Utilities"),
   ];
   if let Ok(home) = env::var("HOME") {
     roots.push(PathBuf::from(home).join("Applications"));
   }
+  // 用户在 search_roots.json 中添加的目录排在内置目录之后
+  match load_search_roots() {
+    Ok(extra) => {
+      for root in extra {
+        if !roots.contains(&root) {
+          roots.push(root);
+        }
+      }
+    }
+    Err(err) => logger::warn(format!("读取应用搜索目录失败: {err}")),
+  }
+  roots
+}
+
+fn load_search_roots() -> Result<Vec<PathBuf>, PlatformError> {
+  let stored: Vec<String> = read_json_store(&search_roots_path()?)?;
+  Ok(stored.into_iter().map(PathBuf::from).collect())
+}
+
+fn set_search_roots_impl(paths: Vec<String>) -> Result<Vec<String>, PlatformError> {
+  let mut roots: Vec<String> = Vec::with_capacity(paths.len());
+  for raw in paths.iter().filter(|raw| !raw.trim().is_empty()) {
+    let root = expand_user_path(raw)?;
+    if !root.is_dir() {
+      return Err(PlatformError::InvalidSelection(format!(
+        "搜索目录不存在或不是文件夹: {}",
+        root.display()
+      )));
+    }
+    let root = root.display().to_string();
+    if !roots.contains(&root) {
+      roots.push(root);
+    }
+  }
+
+  write_json_store(&search_roots_path()?, &roots)?;
+
+  // 已缓存的应用列表不包含新目录中的应用
+  clear_bundle_cache_impl();
+  Ok(roots)
+}
+
+fn find_app_in_common_locations(bundle_id: &str) -> Option<PathBuf> {
+  let include_nested = load_settings().include_nested_apps;
+  for root in application_roots() {
+    let mut apps = Vec::new();
+    collect_apps(&root, 2, include_nested, &mut apps);
+    // First, match by CFBundleIdentifier
+    for path in &apps {
+      let info_path = path.join("Contents").join("Info.plist");
+      if let Ok(value) = Value::from_file(&info_path) {
+        if let Some(dict) = value.as_dictionary() {
+          let id = dict.get("CFBundleIdentifier").and_then(Value::as_string);
+          if let Some(id) = id {
+            let a = id.to_ascii_lowercase();
+            let b = bundle_id.to_ascii_lowercase();
+            if a == b || a.ends_with(&b) || b.ends_with(&a) {
+              return Some(path.clone());
+            }
+          }
+        }
+      }
+    }
+
+    // Next, match by app folder name or CFBundleName hint
+    let hint = bundle_id.rsplit('.').next().unwrap_or(bundle_id).to_ascii_lowercase();
+    for path in apps {
+      let stem = path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_ascii_lowercase());
+      if stem.as_deref().map(|s| s.contains(&hint)).unwrap_or(false) {
+        return Some(path);
+      }
+      let info_path = path.join("Contents").join("Info.plist");
+      if let Ok(value) = Value::from_file(&info_path) {
+        if let Some(dict) = value.as_dictionary() {
+          let name = dict.get("CFBundleName").and_then(Value::as_string);
+          if let Some(name) = name {
+            if name.to_ascii_lowercase().contains(&hint) {
+              return Some(path);
+            }
+          }
+        }
+      }
+    }
+  }
+  None
+}
+
+fn collect_apps(root: &Path, depth: usize, include_nested: bool, acc: &mut Vec<PathBuf>) {
+  if depth == 0 {
+    return;
+  }
+  if let Ok(read_dir) = fs::read_dir(root) {
+    for entry in read_dir.flatten() {
+      let path = entry.path();
+      if path.extension().map(|e| e.eq_ignore_ascii_case("app")).unwrap_or(false) {
+        // 不进入 .app 内部继续查找，其中的登录项、XPC 服务等辅助应用不能作为打开方式
+        if include_nested || !is_inside_app_bundle(&path) {
+          acc.push(path);
+        }
+      } else if path.is_dir() {
+        collect_apps(&path, depth - 1, include_nested, acc);
+      }
+    }
+  }
+}
+
+// 根目录本身位于某个应用包内时（例如 Xcode.app/Contents/Applications），其中的应用都是辅助应用
+fn is_inside_app_bundle(path: &Path) -> bool {
+  path
+    .ancestors()
+    .skip(1)
+    .any(|ancestor| ancestor.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("app")))
+}
+
+fn read_info_dictionary(app_path: &Path) -> Option<Dictionary> {
+  Value::from_file(app_path.join("Contents").join("Info.plist"))
+    .ok()?
+    .into_dictionary()
+}
+
+fn read_app_display_name(info_dict: &Dictionary, fallback: &Path) -> String {
+  // 优先使用 Info.plist 中的显示名称，其次使用 CFBundleName，最后退回到包文件夹名
+  if let Some(name) = info_dict
+    .get("CFBundleDisplayName")
+    .and_then(Value::as_string)
+    .map(|s| s.to_string())
+  {
+    return name;
+  }
+
+  if let Some(name) = info_dict
+    .get("CFBundleName")
+    .and_then(Value::as_string)
+    .map(|s| s.to_string())
+  {
+    return name;
+  }
+
+  fallback
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .unwrap_or("未知应用")
+    .to_string()
+}
+
+fn application_name_from_path(app_path: &Path) -> Result<String, PlatformError> {
+  // Prefer Info.plist values; fallback to Spotlight display name; finally use folder name
+  let info_path = app_path.join("Contents").join("Info.plist");
+  match Value::from_file(&info_path) {
+    Ok(info_value) => {
+      if let Some(dict) = info_value.as_dictionary() {
+        return Ok(read_app_display_name(dict, app_path));
+      }
+    }
+    Err(_) => {}
+  }
+
+  // Fallback via mdls metadata
+  if let Some(name) = mdls_display_name(app_path) {
+    return Ok(name);
+  }
+
+  Ok(
+    app_path
+      .file_stem()
+      .and_then(|s| s.to_str())
+      .unwrap_or("未知应用")
+      .to_string(),
+  )
+}
+
+fn app_icon_base64(app_path: &Path) -> Option<String> {
+  let cache = ICON_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+  if let Some(cached) = cache.lock().ok()?.get(app_path) {
+    return cached.clone();
+  }
+
+  let icon = icns_path_for_app(app_path)
+    .and_then(|icns_path| convert_icns_to_png(&icns_path, None))
+    .map(|png| BASE64.encode(png));
+
+  if let Ok(mut cache) = cache.lock() {
+    cache.insert(app_path.to_path_buf(), icon.clone());
+  }
+  icon
+}
+
+fn get_app_icon_impl(application_path: String, size: u32) -> Result<String, PlatformError> {
+  if !(MIN_ICON_SIZE..=MAX_ICON_SIZE).contains(&size) {
+    return Err(PlatformError::InvalidSelection(format!(
+      "图标尺寸需在 {MIN_ICON_SIZE} 到 {MAX_ICON_SIZE} 之间: {size}"
+    )));
+  }
+
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let cache_path = icon_cache_path(&app_path, size)?;
+  if let Ok(png) = fs::read(&cache_path) {
+    return Ok(BASE64.encode(png));
+  }
+
+  let png = icns_path_for_app(&app_path)
+    .and_then(|icns_path| convert_icns_to_png(&icns_path, Some(size)))
+    .ok_or_else(|| PlatformError::MissingInfo(format!("未找到应用图标: {}", app_path.display())))?;
+
+  if let Some(dir) = cache_path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  fs::write(&cache_path, &png)?;
+  Ok(BASE64.encode(png))
+}
+
+fn icon_cache_path(app_path: &Path, size: u32) -> Result<PathBuf, PlatformError> {
+  // 应用更新后包的修改时间会变化，以路径和修改时间作为键即可让旧缓存自然失效
+  let modified = fs::metadata(app_path)?
+    .modified()
+    .ok()
+    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_secs())
+    .unwrap_or(0);
+
+  let mut hasher = DefaultHasher::new();
+  app_path.hash(&mut hasher);
+  modified.hash(&mut hasher);
+  Ok(icons_dir_path()?.join(format!("{:016x}-{size}.png", hasher.finish())))
+}
+
+fn icns_path_for_app(app_path: &Path) -> Option<PathBuf> {
+  let info_dict = read_info_dictionary(app_path)?;
+  let resources = app_path.join("Contents").join("Resources");
+
+  // CFBundleIconFile 可以省略 .icns 后缀；CFBundleIconName 通常与资源文件同名
+  ["CFBundleIconFile", "CFBundleIconName"]
+    .iter()
+    .filter_map(|key| info_dict.get(key).and_then(Value::as_string))
+    .map(|name| {
+      let path = resources.join(name);
+      if path.extension().is_some() {
+        path
+      } else {
+        path.with_extension("icns")
+      }
+    })
+    .find(|path| path.is_file())
+}
+
+fn convert_icns_to_png(icns_path: &Path, size: Option<u32>) -> Option<Vec<u8>> {
+  let output_path = env::temp_dir().join(format!(
+    "default-application-icon-{}-{}.png",
+    std::process::id(),
+    ICON_CONVERSION_COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+
+  let mut command = Command::new("sips");
+  command.arg("-s").arg("format").arg("png");
+  // sips 会选取 icns 中最大的一份图像，再按最长边缩放到请求的尺寸
+  if let Some(size) = size {
+    command.arg("-Z").arg(size.to_string());
+  }
+  let status = command
+    .arg(icns_path)
+    .arg("--out")
+    .arg(&output_path)
+    .output()
+    .ok()?
+    .status;
+
+  let png = if status.success() {
+    fs::read(&output_path).ok()
+  } else {
+    None
+  };
+  let _ = fs::remove_file(&output_path);
+  png
+}
+
+fn mdls_display_name(app_path: &Path) -> Option<String> {
+  let output = output_with_timeout(
+    Command::new("mdls")
+      .arg("-name")
+      .arg("kMDItemDisplayName")
+      .arg("-raw")
+      .arg(app_path),
+    MDFIND_TIMEOUT,
+  )?;
+
+  if !output.status.success() {
+    return None;
+  }
+  let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  if text.is_empty() || text == "(null)" {
+    None
+  } else {
+    Some(text)
+  }
+}
+
+fn registered_bundle_id_from_path(app_path: &Path) -> Result<String, PlatformError> {
+  let bundle_id = bundle_id_from_path(app_path)?;
+
+  // 同一 bundle id 可能有多个副本，必须确认用户选择的这一份就是系统登记的路径
+  let is_registered =
+    || path_is_registered(app_path, &application_paths_for_bundle_id(&bundle_id));
+
+  // 刚拖进“应用程序”文件夹的应用可能还没被系统登记，先主动注册一次再检查
+  if !is_registered() && (!register_application_bundle(app_path) || !is_registered()) {
+    // 直接从磁盘映像等位置选择、尚未安装的应用没有在 LaunchServices 中注册，写入后会指向不存在的应用
+    return Err(PlatformError::MissingInfo(format!(
+      "{} 没有作为 {bundle_id} 在系统中注册，请先将应用安装到“应用程序”文件夹",
+      app_path.display()
+    )));
+  }
+  Ok(bundle_id)
+}
+
+fn path_is_registered(app_path: &Path, registered: &[PathBuf]) -> bool {
+  let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  let app_path = canonical(app_path);
+  registered.iter().any(|path| canonical(path) == app_path)
+}
+
+fn register_application_bundle(app_path: &Path) -> bool {
+  // 磁盘映像中的应用不应被登记为默认应用，保持原来的报错
+  if app_path.starts_with("/Volumes") {
+    return false;
+  }
+  match Command::new(LSREGISTER_PATH).arg("-f").arg(app_path).output() {
+    Ok(output) if output.status.success() => {
+      logger::info(format!("已使用 lsregister 注册 {}", app_path.display()));
+      true
+    }
+    Ok(output) => {
+      logger::warn(format!(
+        "lsregister 注册 {} 失败: {}",
+        app_path.display(),
+        String::from_utf8_lossy(&output.stderr).trim()
+      ));
+      false
+    }
+    Err(err) => {
+      logger::warn(format!("无法执行 lsregister: {err}"));
+      false
+    }
+  }
+}
+
+fn bundle_id_from_path(app_path: &Path) -> Result<String, PlatformError> {
+  let info_path = app_path.join("Contents").join("Info.plist");
+  let info_value = Value::from_file(&info_path)?;
+  let dict = info_value
+    .as_dictionary()
+    .ok_or_else(|| PlatformError::MissingInfo("Info.plist 结构无效".into()))?;
+
+  dict
+    .get("CFBundleIdentifier")
+    .and_then(Value::as_string)
+    .map(|s| s.to_string())
+    .ok_or_else(|| PlatformError::MissingInfo("缺少 CFBundleIdentifier".into()))
+}
+
+fn ensure_extension_normalized(ext: &str) -> String {
+  let normalized = ext.trim_start_matches('.').to_lowercase();
+
+  // LaunchServices 只按最后一段后缀匹配，tar.gz、d.ts 这类多段后缀取最后一段
+  match normalized.rsplit_once('.') {
+    Some((_, last)) => last.to_string(),
+    None => normalized,
+  }
+}
+
+fn list_file_associations_impl() -> Result<Vec<FileAssociation>, PlatformError> {
+  let mut results = Vec::new();
+  list_file_associations_streaming_impl(|progress| results.push(progress.association))?;
+  Ok(results)
+}
+
+fn list_file_associations_streaming_impl(
+  mut on_resolved: impl FnMut(AssociationProgress),
+) -> Result<usize, PlatformError> {
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+
+  let extensions = ordered_extension_list()?;
+  let total = extensions.len();
+  for (index, ext) in extensions.iter().enumerate() {
+    on_resolved(AssociationProgress {
+      index,
+      total,
+      association: resolve_association(handlers, ext),
+    });
+  }
+
+  Ok(total)
+}
+
+fn list_file_associations_with_icons_impl() -> Result<Vec<FileAssociation>, PlatformError> {
+  let mut associations = list_file_associations_impl()?;
+  for association in &mut associations {
+    let app_path = Path::new(&association.application_path);
+    if app_path.is_dir() {
+      association.icon_base64 = app_icon_base64(app_path);
+    }
+  }
+  Ok(associations)
+}
+
+fn get_association_for_extension_impl(extension: String) -> Result<FileAssociation, PlatformError> {
+  let normalized = ensure_extension_normalized(&extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+  Ok(resolve_association(handlers, &normalized))
+}
+
+fn resolve_association(handlers: &[Value], ext: &str) -> FileAssociation {
+  let handler_bundle_id = find_bundle_id_for_extension(handlers, ext);
+  // LSHandlers 中有该扩展名的记录却没有任何角色处理程序，系统会退回到默认应用
+  let handler_missing = handler_bundle_id.is_none() && has_handler_entry(handlers, ext);
+
+  let is_pinned = is_extension_pinned(ext);
+  let note = extension_note(ext);
+  let (viewer_bundle_id, editor_bundle_id) = split_role_handlers(handlers, ext);
+  // 展示实际使用的类型；系统只给出 dyn. 动态类型时留空，而不是显示猜测或动态的类型
+  let content_type = resolved_content_type(ext);
+
+  // 即使用户设置了自定义应用，也一并给出 LaunchServices 报告的默认应用，方便判断重置后的结果
+  let system_default_name = system_default_bundle_id_for_extension(ext).map(|system_id| {
+    bundle_path_from_id(&system_id)
+      .and_then(|path| application_name_from_path(&path))
+      .unwrap_or_else(|_| humanize_bundle_id(&system_id))
+  });
+
+  // plist 只是覆盖层，优先使用 LaunchServices 实际会打开的应用，同时省去 mdfind 查找路径
+  if let Some(path) = default_application_path_for_content_type(&lookup_content_type(ext)) {
+    let live_bundle_id = bundle_id_from_path(&path).ok();
+    let overridden = handler_bundle_id
+      .as_deref()
+      .zip(live_bundle_id.as_deref())
+      .is_some_and(|(handler, live)| handler.eq_ignore_ascii_case(live));
+    let display_name = application_name_from_path(&path)
+      .ok()
+      .or_else(|| live_bundle_id.clone())
+      .unwrap_or_else(|| path.display().to_string());
+    return FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: display_name,
+      application_path: path.display().to_string(),
+      status: if handler_missing {
+        AssociationStatus::HandlerMissing
+      } else {
+        AssociationStatus::Resolved
+      },
+      source: if overridden {
+        AssociationSource::UserOverride
+      } else {
+        AssociationSource::SystemDefault
+      },
+      is_user_override: overridden,
+      system_default_name,
+      bundle_identifier: live_bundle_id.unwrap_or_default(),
+      is_pinned,
+      note,
+      viewer_bundle_id,
+      editor_bundle_id,
+      content_type,
+      ..Default::default()
+    };
+  }
+
+  let (bundle_id, source) = match handler_bundle_id {
+    Some(bundle_id) => (Some(bundle_id), AssociationSource::UserOverride),
+    None => (system_default_bundle_id_for_extension(ext), AssociationSource::SystemDefault),
+  };
+
+  let Some(bundle_id) = bundle_id else {
+    return FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: "未设置默认应用".into(),
+      application_path: String::new(),
+      status: if handler_missing {
+        AssociationStatus::HandlerMissing
+      } else {
+        AssociationStatus::Unset
+      },
+      // 既没有用户设置也没有系统默认应用
+      source: AssociationSource::None,
+      system_default_name,
+      is_pinned,
+      note,
+      viewer_bundle_id,
+      editor_bundle_id,
+      content_type,
+      ..Default::default()
+    };
+  };
+
+  match bundle_path_from_id(&bundle_id) {
+    Ok(path) => {
+      let display_name = application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone());
+      FileAssociation {
+        extension: ext.to_string(),
+        category: category_for_extension(ext),
+        application_name: display_name,
+        application_path: path.display().to_string(),
+        status: if handler_missing {
+          AssociationStatus::HandlerMissing
+        } else {
+          AssociationStatus::Resolved
+        },
+        source,
+        is_user_override: source == AssociationSource::UserOverride,
+        system_default_name,
+        bundle_identifier: bundle_id,
+        is_pinned,
+        note,
+        viewer_bundle_id,
+        editor_bundle_id,
+        content_type,
+        ..Default::default()
+      }
+    }
+    Err(_) => FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: humanize_bundle_id(&bundle_id),
+      application_path: String::new(),
+      status: AssociationStatus::AppNotFound,
+      source,
+      is_user_override: source == AssociationSource::UserOverride,
+      system_default_name,
+      bundle_identifier: bundle_id,
+      is_pinned,
+      note,
+      viewer_bundle_id,
+      editor_bundle_id,
+      content_type,
+      ..Default::default()
+    },
+  }
+}
+
+fn list_handlers_for_extension_impl(
+  extension: String,
+) -> Result<Vec<FileAssociation>, PlatformError> {
+  let normalized = ensure_extension_normalized(&extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  let mut seen = BTreeSet::new();
+  let mut results = Vec::new();
+  for bundle_id in copy_all_handlers_for_content_type(&lookup_content_type(&normalized)) {
+    if !seen.insert(bundle_id.to_ascii_lowercase()) {
+      continue;
+    }
+    // 找不到安装路径的处理程序无法被选为默认应用，直接跳过
+    let Ok(path) = bundle_path_from_id(&bundle_id) else {
+      continue;
+    };
+    let display_name = application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone());
+    results.push(FileAssociation {
+      extension: normalized.clone(),
+      category: category_for_extension(&normalized),
+      application_name: display_name,
+      application_path: path.display().to_string(),
+      bundle_identifier: bundle_id,
+      status: AssociationStatus::Resolved,
+      ..Default::default()
+    });
+  }
+
+  Ok(results)
+}
+
+fn list_all_handlers_for_extension_impl(
+  extension: String,
+) -> Result<Vec<CandidateApplication>, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+  let current = current_bundle_id_for_extension(handlers, &normalized);
+
+  // 与 list_handlers_for_extension 使用同一份 LaunchServices 处理程序列表，只额外标记当前默认应用
+  let mut results: Vec<CandidateApplication> = list_handlers_for_extension_impl(normalized)?
+    .into_iter()
+    .map(|handler| CandidateApplication {
+      is_default: current
+        .as_deref()
+        .is_some_and(|current| current.eq_ignore_ascii_case(&handler.bundle_identifier)),
+      application_name: handler.application_name,
+      application_path: handler.application_path,
+      bundle_identifier: handler.bundle_identifier,
+    })
+    .collect();
+
+  // 当前默认应用排在最前面，其余保持 LaunchServices 返回的顺序
+  results.sort_by_key(|candidate| !candidate.is_default);
+  Ok(results)
+}
+
+fn get_bundle_info_impl(application_path: String) -> Result<BundleInfo, PlatformError> {
+  let bundle_path = resolve_app_bundle_path(&application_path)?;
+  let info_dict = read_info_dictionary(&bundle_path)
+    .ok_or_else(|| PlatformError::MissingInfo("无法读取 Info.plist".into()))?;
+
+  let string_value = |key: &str| {
+    info_dict
+      .get(key)
+      .and_then(Value::as_string)
+      .map(|s| s.to_string())
+  };
+
+  let bundle_identifier = string_value("CFBundleIdentifier")
+    .ok_or_else(|| PlatformError::MissingInfo("缺少 CFBundleIdentifier".into()))?;
+
+  // 不在常规应用目录中的应用（例如下载目录、挂载的磁盘映像）需要在界面上提示用户
+  let in_standard_location = application_roots()
+    .iter()
+    .any(|root| bundle_path.starts_with(root));
+
+  Ok(BundleInfo {
+    display_name: read_app_display_name(&info_dict, &bundle_path),
+    application_path: bundle_path.display().to_string(),
+    bundle_identifier,
+    version: string_value("CFBundleShortVersionString"),
+    minimum_system_version: string_value("LSMinimumSystemVersion"),
+    in_standard_location,
+  })
+}
+
+fn validate_application_impl(
+  application_path: String,
+  extension: String,
+) -> Result<ApplicationVerdict, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+
+  // 应用本身的问题作为结论返回给界面，由用户决定是否继续，而不是直接报错
+  let bundle_path = match resolve_app_bundle_path(&application_path) {
+    Ok(path) => path,
+    Err(err) => {
+      return Ok(ApplicationVerdict {
+        warnings: vec![err.to_string()],
+        ..Default::default()
+      })
+    }
+  };
+  let Some(info_dict) = read_info_dictionary(&bundle_path) else {
+    return Ok(ApplicationVerdict {
+      warnings: vec!["无法读取 Info.plist".into()],
+      ..Default::default()
+    });
+  };
+
+  let mut warnings = Vec::new();
+  let bundle_id = info_dict
+    .get("CFBundleIdentifier")
+    .and_then(Value::as_string)
+    .map(str::to_string);
+  if bundle_id.is_none() {
+    warnings.push("Info.plist 缺少 CFBundleIdentifier".to_string());
+  }
+
+  let declares_type = app_supports_extension(&info_dict, &normalized);
+  if !declares_type {
+    let name = read_app_display_name(&info_dict, &bundle_path);
+    warnings.push(PlatformError::UnsupportedByApp(name, normalized).to_string());
+  }
+
+  if !application_roots()
+    .iter()
+    .any(|root| bundle_path.starts_with(root))
+  {
+    warnings.push("应用不在“应用程序”文件夹中，移动或删除后关联会失效".to_string());
+  }
+
+  let executable = info_dict
+    .get("CFBundleExecutable")
+    .and_then(Value::as_string)
+    .map(|name| bundle_path.join("Contents").join("MacOS").join(name));
+  match executable.map(fs::metadata) {
+    Some(Ok(metadata)) if metadata.permissions().mode() & 0o111 != 0 => {}
+    Some(Ok(_)) => warnings.push("应用的可执行文件没有执行权限".to_string()),
+    _ => warnings.push("找不到应用的可执行文件".to_string()),
+  }
+
+  Ok(ApplicationVerdict {
+    valid: bundle_id.is_some(),
+    bundle_id,
+    declares_type,
+    warnings,
+  })
+}
+
+fn list_installed_applications_impl() -> Result<Vec<InstalledApplication>, PlatformError> {
+  let mut results: Vec<InstalledApplication> = scan_installed_applications()
+    .into_iter()
+    .map(|(app, _)| app)
+    .collect();
+
+  results.sort_by_key(|app| app.application_name.to_lowercase());
+  Ok(results)
+}
+
+fn scan_installed_applications() -> Vec<(InstalledApplication, Dictionary)> {
+  // application_roots 以 /Applications 开头，同一 bundle id 先出现的副本优先保留
+  let mut seen = HashSet::new();
+  let mut results = Vec::new();
+  let include_nested = load_settings().include_nested_apps;
+  for root in application_roots() {
+    let mut apps = Vec::new();
+    collect_apps(&root, 2, include_nested, &mut apps);
+    for path in apps {
+      let Some(dict) = read_info_dictionary(&path) else {
+        continue;
+      };
+      let Some(bundle_id) = dict.get("CFBundleIdentifier").and_then(Value::as_string) else {
+        continue;
+      };
+      if !seen.insert(bundle_id.to_ascii_lowercase()) {
+        continue;
+      }
+      let app = InstalledApplication {
+        application_name: read_app_display_name(&dict, &path),
+        application_path: path.display().to_string(),
+        bundle_identifier: bundle_id.to_string(),
+      };
+      results.push((app, dict));
+    }
+  }
+  results
+}
+
+fn search_applications_impl(query: &str, limit: usize) -> Vec<InstalledApplication> {
+  let Ok(mut index) = APPLICATION_INDEX.lock() else {
+    return Vec::new();
+  };
+  // 扫描应用目录和 Spotlight 较慢，索引只构建一次，手动刷新时随缓存一起清空
+  let apps = index.get_or_insert_with(build_application_index);
+
+  let query = query.trim().to_lowercase();
+  let mut matches: Vec<(u8, &InstalledApplication)> = apps
+    .iter()
+    .filter_map(|app| {
+      let score = [&app.application_name, &app.bundle_identifier]
+        .iter()
+        .filter_map(|field| match_score(&field.to_lowercase(), &query))
+        .min()?;
+      Some((score, app))
+    })
+    .collect();
+
+  matches.sort_by_key(|(score, app)| (*score, app.application_name.to_lowercase()));
+  matches
+    .into_iter()
+    .take(limit)
+    .map(|(_, app)| app.clone())
+    .collect()
+}
+
+// 分值越小越靠前：前缀匹配 < 子串匹配 < 按顺序包含所有字符的模糊匹配
+fn match_score(candidate: &str, query: &str) -> Option<u8> {
+  if candidate.starts_with(query) {
+    return Some(0);
+  }
+  if candidate.contains(query) {
+    return Some(1);
+  }
+  let mut chars = candidate.chars();
+  if query.chars().all(|q| chars.any(|c| c == q)) {
+    return Some(2);
+  }
+  None
+}
+
+fn build_application_index() -> Vec<InstalledApplication> {
+  let mut apps: Vec<InstalledApplication> = scan_installed_applications()
+    .into_iter()
+    .map(|(app, _)| app)
+    .collect();
+
+  // Spotlight 能找到不在常规目录中的应用，例如放在下载目录或外置磁盘里的应用
+  let mut seen: HashSet<String> = apps
+    .iter()
+    .map(|app| app.bundle_identifier.to_ascii_lowercase())
+    .collect();
+  for path in spotlight_application_paths() {
+    let Some(dict) = read_info_dictionary(&path) else {
+      continue;
+    };
+    let Some(bundle_id) = dict.get("CFBundleIdentifier").and_then(Value::as_string) else {
+      continue;
+    };
+    if !seen.insert(bundle_id.to_ascii_lowercase()) {
+      continue;
+    }
+    apps.push(InstalledApplication {
+      application_name: read_app_display_name(&dict, &path),
+      application_path: path.display().to_string(),
+      bundle_identifier: bundle_id.to_string(),
+    });
+  }
+  apps
+}
+
+fn spotlight_application_paths() -> Vec<PathBuf> {
+  // kMDItemKind 会随系统语言本地化，按内容类型查询更可靠
+  let Some(output) = output_with_timeout(
+    Command::new("mdfind").arg("kMDItemContentType == 'com.apple.application-bundle'"),
+    MDFIND_TIMEOUT,
+  ) else {
+    return Vec::new();
+  };
+  if !output.status.success() {
+    return Vec::new();
+  }
+
+  String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty())
+    .map(PathBuf::from)
+    .collect()
+}
+
+fn list_candidate_apps_for_extension_impl(
+  extension: String,
+) -> Result<Vec<CandidateApplication>, PlatformError> {
+  let normalized = ensure_extension_normalized(&extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+  let current = current_bundle_id_for_extension(handlers, &normalized);
+  let content_type = resolved_content_type(&normalized);
+
+  let mut results: Vec<CandidateApplication> = scan_installed_applications()
+    .into_iter()
+    .filter(|(_, dict)| declares_document_type(dict, &normalized, content_type.as_deref()))
+    .map(|(app, _)| {
+      let is_default = current
+        .as_deref()
+        .map(|id| id.eq_ignore_ascii_case(&app.bundle_identifier))
+        .unwrap_or(false);
+      CandidateApplication {
+        application_name: app.application_name,
+        application_path: app.application_path,
+        bundle_identifier: app.bundle_identifier,
+        is_default,
+      }
+    })
+    .collect();
+
+  results.sort_by_key(|app| app.application_name.to_lowercase());
+  Ok(results)
+}
+
+fn declares_document_type(
+  info_dict: &Dictionary,
+  extension: &str,
+  content_type: Option<&str>,
+) -> bool {
+  let Some(document_types) = info_dict.get("CFBundleDocumentTypes").and_then(Value::as_array) else {
+    return false;
+  };
+
+  document_types
+    .iter()
+    .filter_map(Value::as_dictionary)
+    .any(|doc_type| {
+      let declares_extension = doc_type
+        .get("CFBundleTypeExtensions")
+        .and_then(Value::as_array)
+        .map(|items| {
+          items
+            .iter()
+            .filter_map(Value::as_string)
+            .any(|item| ensure_extension_normalized(item) == extension)
+        })
+        .unwrap_or(false);
+
+      let declares_content_type = content_type
+        .and_then(|expected| {
+          doc_type
+            .get("LSItemContentTypes")
+            .and_then(Value::as_array)
+            .map(|items| {
+              items
+                .iter()
+                .filter_map(Value::as_string)
+                .any(|item| declared_type_matches(item, expected))
+            })
+        })
+        .unwrap_or(false);
+
+      declares_extension || declares_content_type
+    })
+}
+
+// 声明了父类型（如 public.movie）的应用同样可以打开其子类型（如 public.mpeg-4）；
+// public.data 等根类型几乎涵盖所有文件，不参与一致性判断，只接受精确匹配
+fn declared_type_matches(declared: &str, expected: &str) -> bool {
+  declared == expected
+    || (!ROOT_CONTENT_TYPES.contains(&declared) && content_type_conforms_to(expected, declared))
+}
+
+fn app_supports_extension(info_dict: &Dictionary, extension: &str) -> bool {
+  let mapped_content_type = resolved_content_type(extension);
+  let content_type = mapped_content_type.as_deref();
+  if declares_document_type(info_dict, extension, content_type) {
+    return true;
+  }
+
+  // 声明了通配类型的应用（例如文本编辑器）可以打开任意文件
+  let declares_wildcard = info_dict
+    .get("CFBundleDocumentTypes")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_dictionary)
+    .any(|doc_type| {
+      let wildcard_extension = doc_type
+        .get("CFBundleTypeExtensions")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_string).any(|item| item == "*"))
+        .unwrap_or(false);
+      let wildcard_type = doc_type
+        .get("LSItemContentTypes")
+        .and_then(Value::as_array)
+        .map(|items| {
+          items
+            .iter()
+            .filter_map(Value::as_string)
+            .any(|item| item == "public.data" || item == "public.item")
+        })
+        .unwrap_or(false);
+      wildcard_extension || wildcard_type
+    });
+  if declares_wildcard {
+    return true;
+  }
+
+  // 应用自己导出的类型声明中也可能包含该扩展名
+  info_dict
+    .get("UTExportedTypeDeclarations")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_dictionary)
+    .any(|declaration| {
+      let declares_identifier = content_type
+        .zip(declaration.get("UTTypeIdentifier").and_then(Value::as_string))
+        .map(|(expected, identifier)| identifier == expected)
+        .unwrap_or(false);
+      let declares_extension = declaration
+        .get("UTTypeTagSpecification")
+        .and_then(Value::as_dictionary)
+        .and_then(|tags| tags.get("public.filename-extension"))
+        .map(|tag| match tag {
+          Value::String(item) => ensure_extension_normalized(item) == extension,
+          Value::Array(items) => items
+            .iter()
+            .filter_map(Value::as_string)
+            .any(|item| ensure_extension_normalized(item) == extension),
+          _ => false,
+        })
+        .unwrap_or(false);
+      declares_identifier || declares_extension
+    })
+}
+
+fn check_app_supports_extension_impl(
+  application_path: String,
+  extension: String,
+) -> Result<bool, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let info_dict = read_info_dictionary(&app_path)
+    .ok_or_else(|| PlatformError::MissingInfo("无法读取 Info.plist".into()))?;
+  Ok(app_supports_extension(&info_dict, &normalized))
+}
+
+fn list_url_scheme_handlers_impl() -> Result<Vec<UrlSchemeAssociation>, PlatformError> {
+  Ok(
+    load_url_scheme_list()?
+      .iter()
+      .map(|scheme| resolve_url_scheme_association(scheme))
+      .collect(),
+  )
+}
+
+fn resolve_url_scheme_association(scheme: &str) -> UrlSchemeAssociation {
+  let Some(bundle_id) = copy_default_handler_for_url_scheme(scheme) else {
+    return UrlSchemeAssociation {
+      scheme: scheme.to_string(),
+      application_name: "未设置默认应用".into(),
+      application_path: String::new(),
+      status: AssociationStatus::Unset,
+    };
+  };
+
+  match bundle_path_from_id(&bundle_id) {
+    Ok(path) => UrlSchemeAssociation {
+      scheme: scheme.to_string(),
+      application_name: application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone()),
+      application_path: path.display().to_string(),
+      status: AssociationStatus::Resolved,
+    },
+    Err(_) => UrlSchemeAssociation {
+      scheme: scheme.to_string(),
+      application_name: humanize_bundle_id(&bundle_id),
+      application_path: String::new(),
+      status: AssociationStatus::AppNotFound,
+    },
+  }
+}
+
+fn set_default_application_for_url_scheme_impl(
+  scheme: String,
+  application_path: String,
+) -> Result<(), PlatformError> {
+  let normalized = validate_url_scheme(&scheme)?;
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let bundle_id = registered_bundle_id_from_path(&app_path)?;
+  set_url_scheme_default(&normalized, &bundle_id)?;
+  register_url_scheme_if_needed(&normalized)
+}
+
+fn validate_url_scheme(scheme: &str) -> Result<String, PlatformError> {
+  let normalized = scheme
+    .trim()
+    .trim_end_matches("://")
+    .trim_end_matches(':')
+    .to_lowercase();
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "URL 协议不能为空".into(),
+    ));
+  }
+
+  // RFC 3986：以字母开头，后续只能是字母、数字、'+'、'-' 或 '.'
+  let mut chars = normalized.chars();
+  let valid = chars.next().map(|c| c.is_ascii_alphabetic()).unwrap_or(false)
+    && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+  if !valid {
+    return Err(PlatformError::InvalidSelection(format!(
+      "URL 协议只能以字母开头，并且只能包含字母、数字、+、- 或 .: {normalized}"
+    )));
+  }
+
+  Ok(normalized)
+}
+
+fn set_default_browser_impl(
+  application_path: String,
+) -> Result<DefaultHandlerRequest, PlatformError> {
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let bundle_id = registered_bundle_id_from_path(&app_path)?;
+  for scheme in BROWSER_URL_SCHEMES {
+    set_url_scheme_default(scheme, &bundle_id)?;
+  }
+
+  // 修改默认浏览器时系统会弹出确认对话框，调用返回时用户可能尚未确认
+  let applied = BROWSER_URL_SCHEMES.iter().all(|scheme| {
+    copy_default_handler_for_url_scheme(scheme)
+      .map(|current| current.eq_ignore_ascii_case(&bundle_id))
+      .unwrap_or(false)
+  });
+
+  Ok(DefaultHandlerRequest {
+    bundle_identifier: bundle_id,
+    applied,
+  })
+}
+
+fn set_default_mail_client_impl(
+  application_path: String,
+) -> Result<DefaultHandlerRequest, PlatformError> {
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let info_dict = read_info_dictionary(&app_path)
+    .ok_or_else(|| PlatformError::MissingInfo("无法读取 Info.plist".into()))?;
+
+  if !declares_url_scheme(&info_dict, MAIL_URL_SCHEME) {
+    return Err(PlatformError::InvalidSelection(format!(
+      "{} 没有声明支持 mailto 协议",
+      read_app_display_name(&info_dict, &app_path)
+    )));
+  }
+
+  let bundle_id = registered_bundle_id_from_path(&app_path)?;
+  set_url_scheme_default(MAIL_URL_SCHEME, &bundle_id)?;
+
+  let applied = copy_default_handler_for_url_scheme(MAIL_URL_SCHEME)
+    .map(|current| current.eq_ignore_ascii_case(&bundle_id))
+    .unwrap_or(false);
+
+  Ok(DefaultHandlerRequest {
+    bundle_identifier: bundle_id,
+    applied,
+  })
+}
+
+fn declares_url_scheme(info_dict: &Dictionary, scheme: &str) -> bool {
+  let Some(url_types) = info_dict.get("CFBundleURLTypes").and_then(Value::as_array) else {
+    return false;
+  };
+
+  url_types
+    .iter()
+    .filter_map(Value::as_dictionary)
+    .filter_map(|url_type| url_type.get("CFBundleURLSchemes").and_then(Value::as_array))
+    .flatten()
+    .filter_map(Value::as_string)
+    .any(|declared| declared.eq_ignore_ascii_case(scheme))
+}
+
+fn build_association_export() -> Result<AssociationExport, PlatformError> {
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+  let extensions = load_extension_list()?;
+
+  let mut associations = Vec::new();
+  for ext in &extensions {
+    // 只导出用户自定义的关联；系统默认值在导入时会变成固定的覆盖，换一台机器后反而不再跟随系统
+    let association = resolve_association(handlers, ext);
+    for (role, bundle_id) in recorded_role_handlers(handlers, ext) {
+      // 查看/编辑角色按原角色导出，导入时不会变成覆盖全部角色
+      if role == HandlerRole::All {
+        if association.source != AssociationSource::UserOverride
+          || association.bundle_identifier.is_empty()
+        {
+          continue;
+        }
+        associations.push(ExportedAssociation {
+          extension: ext.clone(),
+          bundle_identifier: association.bundle_identifier.clone(),
+          application_name: association.application_name.clone(),
+          application_path: association.application_path.clone(),
+          role,
+        });
+        continue;
+      }
+      let app_path = bundle_path_from_id(&bundle_id).ok();
+      associations.push(ExportedAssociation {
+        extension: ext.clone(),
+        application_name: app_path
+          .as_deref()
+          .and_then(|path| application_name_from_path(path).ok())
+          .unwrap_or_else(|| humanize_bundle_id(&bundle_id)),
+        application_path: app_path
+          .map(|path| path.display().to_string())
+          .unwrap_or_default(),
+        bundle_identifier: bundle_id,
+        role,
+      });
+    }
+  }
+
+  // 内置扩展名在每台机器上都会存在，只需要导出用户自行添加的部分
+  let custom_extensions = extensions
+    .into_iter()
+    .filter(|ext| !default_extensions().any(|default| default.eq_ignore_ascii_case(ext)))
+    .collect();
+
+  Ok(AssociationExport {
+    version: EXPORT_SCHEMA_VERSION,
+    extensions: custom_extensions,
+    associations,
+  })
+}
+
+fn export_associations_impl() -> Result<String, PlatformError> {
+  let export = build_association_export()?;
+  let payload =
+    serde_json::to_string_pretty(&export).map_err(|err| PlatformError::Config(err.to_string()))?;
+
+  // 导出快照与预设分开存放，并像备份一样只保留最近的若干份
+  let dir = exports_dir_path()?;
+  fs::create_dir_all(&dir)?;
+  fs::write(dir.join(format!("export-{}.json", unix_timestamp_millis())), &payload)?;
+  prune_export_snapshots(&dir)?;
+
+  Ok(payload)
+}
+
+fn prune_export_snapshots(dir: &Path) -> Result<(), PlatformError> {
+  let mut snapshots: Vec<(u64, PathBuf)> = fs::read_dir(dir)?
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.path();
+      let stamp = path
+        .file_name()?
+        .to_str()?
+        .strip_prefix("export-")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()?;
+      Some((stamp, path))
+    })
+    .collect();
+
+  snapshots.sort_by_key(|(stamp, _)| std::cmp::Reverse(*stamp));
+  for (_, path) in snapshots.into_iter().skip(MAX_EXPORT_SNAPSHOTS) {
+    fs::remove_file(path)?;
+  }
+  Ok(())
+}
+
+fn export_associations_to_file_impl(path: String) -> Result<usize, PlatformError> {
+  if path.trim().is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "导出路径不能为空".into(),
+    ));
+  }
+
+  let target = expand_user_path(&path)?;
+  let export = build_association_export()?;
+  write_json_store(&target, &export)?;
+
+  Ok(export.associations.len())
+}
+
+fn export_duti_config_impl(path: String) -> Result<usize, PlatformError> {
+  if path.trim().is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "导出路径不能为空".into(),
+    ));
+  }
+
+  let target = expand_user_path(&path)?;
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+
+  // duti 设置文件每行为 “bundle id<TAB>UTI 或扩展名<TAB>角色”，# 开头的行会被忽略
+  let mut payload = String::from("# duti settings exported by DefaultApplication\n");
+  let mut written = BTreeSet::new();
+  for ext in ordered_extension_list()? {
+    // 按 plist 中记录的角色写出，只设置了查看或编辑角色时不会变成 all
+    let mut role_handlers = recorded_role_handlers(handlers, &ext);
+    if role_handlers.is_empty() {
+      match system_default_bundle_id_for_extension(&ext) {
+        Some(bundle_id) => role_handlers.push((HandlerRole::All, bundle_id)),
+        None => {
+          payload.push_str(&format!("# .{ext}: 无法确定默认应用\n"));
+          continue;
+        }
+      }
+    }
+    // duti 把不以 . 开头的名称当作 UTI，没有 UTI 时必须写成 .扩展名
+    let target_type = resolved_content_type(&ext).unwrap_or_else(|| format!(".{ext}"));
+    for (role, bundle_id) in role_handlers {
+      // jpg/jpeg 等扩展名共用同一个 UTI，同一角色只写一行
+      if written.insert((target_type.clone(), role_plist_key(role))) {
+        payload.push_str(&format!("{bundle_id}\t{target_type}\t{}\n", duti_role_name(role)));
+      }
+    }
+  }
+
+  if let Some(parent) = target.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(&target, payload)?;
+
+  Ok(written.len())
+}
+
+fn import_associations_impl(json: String, dry_run: bool) -> Result<ImportReport, PlatformError> {
+  let export: AssociationExport =
+    serde_json::from_str(&json).map_err(|err| PlatformError::Config(err.to_string()))?;
+  import_association_export(export, dry_run)
+}
+
+fn import_associations_from_file_impl(
+  path: String,
+  dry_run: bool,
+) -> Result<ImportReport, PlatformError> {
+  let source = expand_user_path(&path)?;
+  if !source.is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "导入文件不存在: {}",
+      source.display()
+    )));
+  }
+
+  let text = fs::read_to_string(&source)?;
+  import_associations_impl(text, dry_run)
+}
+
+fn import_duti_config_impl(path: String, dry_run: bool) -> Result<ImportReport, PlatformError> {
+  let source = expand_user_path(&path)?;
+  if !source.is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "导入文件不存在: {}",
+      source.display()
+    )));
+  }
+
+  let text = fs::read_to_string(&source)?;
+  let mut associations = Vec::new();
+  let mut skipped = Vec::new();
+  for line in text.lines() {
+    let (bundle_id, target, handler_role) = match parse_duti_line(line) {
+      Some(Ok(fields)) => fields,
+      Some(Err(entry)) => {
+        skipped.push(entry);
+        continue;
+      }
+      None => continue,
+    };
+
+    let extensions = duti_target_extensions(target);
+    if extensions.is_empty() {
+      skipped.push(SkippedEntry {
+        extension: target.to_string(),
+        reason: "无法将该内容类型对应到扩展名".into(),
+      });
+      continue;
+    }
+    // 应用路径留空，导入时按 bundle id 查找本机安装的位置
+    for extension in extensions {
+      associations.push(ExportedAssociation {
+        extension,
+        bundle_identifier: bundle_id.to_string(),
+        application_name: String::new(),
+        application_path: String::new(),
+        role: handler_role,
+      });
+    }
+  }
+
+  let export = AssociationExport {
+    version: EXPORT_SCHEMA_VERSION,
+    extensions: Vec::new(),
+    associations,
+  };
+  let mut report = import_association_export(export, dry_run)?;
+  report.skipped.extend(skipped);
+  Ok(report)
+}
+
+// 空行和注释返回 None；无法导入的行返回跳过原因
+fn parse_duti_line(line: &str) -> Option<Result<(&str, &str, HandlerRole), SkippedEntry>> {
+  // # 之后都是注释，包括写在有效配置行末尾的注释
+  let line = line.split('#').next().unwrap_or_default().trim();
+  if line.is_empty() {
+    return None;
+  }
+
+  let fields: Vec<&str> = line.split_whitespace().collect();
+  let skip = |extension: &str, reason: String| {
+    Some(Err(SkippedEntry {
+      extension: extension.to_string(),
+      reason,
+    }))
+  };
+  let (bundle_id, target, role) = match fields[..] {
+    [bundle_id, target, role] => (bundle_id, target, role),
+    // 两列的行是 URL scheme 设置，这里只处理文件类型
+    [_, scheme] => return skip(scheme, "URL scheme 设置不会被导入".into()),
+    _ => return skip(line, "无法识别的 duti 配置行".into()),
+  };
+  let handler_role = match role.to_ascii_lowercase().as_str() {
+    "all" => HandlerRole::All,
+    "viewer" => HandlerRole::Viewer,
+    "editor" => HandlerRole::Editor,
+    // shell 与 none 是 duti 支持但本应用不会写入的角色，跳过而不是当作 all 应用
+    "shell" | "none" => return skip(target, format!("不支持的角色: {role}")),
+    _ => return skip(target, format!("未知的角色: {role}")),
+  };
+  Some(Ok((bundle_id, target, handler_role)))
+}
+
+fn duti_role_name(role: HandlerRole) -> &'static str {
+  match role {
+    HandlerRole::All => "all",
+    HandlerRole::Viewer => "viewer",
+    HandlerRole::Editor => "editor",
+  }
+}
+
+// duti 中以 . 开头的是扩展名，其余按 UTI 处理；不含 . 的裸名称视为扩展名
+fn duti_target_extensions(target: &str) -> Vec<String> {
+  if let Some(extension) = target.strip_prefix('.') {
+    return vec![ensure_extension_normalized(extension)];
+  }
+  let extensions = extensions_for_content_type(target);
+  if !extensions.is_empty() || target.contains('.') {
+    return extensions;
+  }
+  vec![ensure_extension_normalized(target)]
+}
+
+fn import_association_export(
+  export: AssociationExport,
+  dry_run: bool,
+) -> Result<ImportReport, PlatformError> {
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+
+  let mut changes = Vec::new();
+  let mut live_updates = Vec::new();
+  let mut skipped = Vec::new();
+  for entry in export.associations {
+    let extension = match validate_extension(&entry.extension) {
+      Ok(extension) => extension,
+      Err(err) => {
+        skipped.push(SkippedEntry {
+          extension: entry.extension,
+          reason: err.to_string(),
+        });
+        continue;
+      }
+    };
+
+    // 导出文件中的路径可能来自其他机器，路径无效时再按 bundle id 查找
+    let app_path = locate_exported_application(&entry);
+    let Some(app_path) = app_path else {
+      skipped.push(SkippedEntry {
+        extension,
+        reason: format!("本机未安装应用 {}", entry.bundle_identifier),
+      });
+      continue;
+    };
+
+    let current = match entry.role {
+      HandlerRole::All => current_bundle_id_for_extension(handlers, &extension),
+      role => find_role_bundle_id_for_extension(handlers, &extension, role),
+    };
+    if current
+      .as_deref()
+      .map(|id| id.eq_ignore_ascii_case(&entry.bundle_identifier))
+      .unwrap_or(false)
+    {
+      continue;
+    }
+
+    // 这里只修改 plist 副本，LaunchServices 在 plist 保存成功后再统一更新
+    if !dry_run {
+      upsert_default_application(handlers, &extension, &entry.bundle_identifier, entry.role);
+      live_updates.push((extension.clone(), entry.bundle_identifier.clone(), entry.role));
+    }
+    changes.push(AssociationChange {
+      extension,
+      current_bundle_identifier: current,
+      new_bundle_identifier: entry.bundle_identifier,
+      new_application_path: app_path.display().to_string(),
+    });
+  }
+
+  if !dry_run {
+    let mut tracked: Vec<String> = export
+      .extensions
+      .iter()
+      .filter_map(|extension| validate_extension(extension).ok())
+      .collect();
+    tracked.extend(changes.iter().map(|change| change.extension.clone()));
+    register_extensions_if_needed(&tracked)?;
+
+    if !changes.is_empty() {
+      // 先保存 plist，保存失败时 LaunchServices 保持原样，两者不会不一致
+      save_launch_services_value(&value)?;
+      apply_live_updates(&live_updates);
+      restart_preferences_daemon();
+    }
+  }
+
+  Ok(ImportReport { changes, skipped })
+}
+
+fn locate_exported_application(entry: &ExportedAssociation) -> Option<PathBuf> {
+  let recorded = PathBuf::from(&entry.application_path);
+  let recorded_matches = bundle_id_from_path(&recorded)
+    .map(|id| id.eq_ignore_ascii_case(&entry.bundle_identifier))
+    .unwrap_or(false);
+  if recorded_matches {
+    return Some(recorded);
+  }
+  bundle_path_from_id(&entry.bundle_identifier).ok()
+}
+
+fn add_extension_impl(extension: String) -> Result<Vec<FileAssociation>, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  register_extension_if_needed(&normalized)?;
+  list_file_associations_impl()
+}
+
+fn add_extensions_impl(extensions: Vec<String>) -> Result<AddExtensionsResult, PlatformError> {
+  let mut known: BTreeSet<String> = load_extension_list()?.into_iter().collect();
+  let mut added = Vec::new();
+  let mut results = Vec::with_capacity(extensions.len());
+
+  for input in extensions {
+    let outcome = match validate_extension(&input) {
+      Ok(normalized) if known.insert(normalized.clone()) => {
+        added.push(normalized.clone());
+        AddExtensionOutcome {
+          input,
+          extension: normalized,
+          status: AddExtensionStatus::Added,
+          reason: None,
+        }
+      }
+      Ok(normalized) => AddExtensionOutcome {
+        input,
+        extension: normalized,
+        status: AddExtensionStatus::AlreadyAdded,
+        reason: Some("已在列表中".into()),
+      },
+      Err(err) => AddExtensionOutcome {
+        extension: ensure_extension_normalized(&input),
+        input,
+        status: AddExtensionStatus::Rejected,
+        reason: Some(err.to_string()),
+      },
+    };
+    results.push(outcome);
+  }
+
+  // 所有有效的扩展名只写一次配置文件
+  if !added.is_empty() {
+    register_extensions_if_needed(&added)?;
+  }
+
+  Ok(AddExtensionsResult {
+    associations: list_file_associations_impl()?,
+    results,
+  })
+}
+
+fn validate_extension(extension: &str) -> Result<String, PlatformError> {
+  let normalized = ensure_extension_normalized(extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  if !normalized
+    .chars()
+    .all(|ch| ch.is_ascii_alphanumeric() || ch == '+' || ch == '-')
+  {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名只能包含字母、数字、加号或减号".into(),
+    ));
+  }
+
+  Ok(normalized)
+}
+
+fn remove_extension_impl(extension: String) -> Result<Vec<FileAssociation>, PlatformError> {
+  let normalized = validate_removable_extension(&extension)?;
+
+  // 未在列表中的扩展名直接忽略；只从跟踪列表中移除，不改动 LaunchServices 中已有的默认应用设置
+  unregister_extension(&normalized)?;
+  list_file_associations_impl()
+}
+
+fn remove_extension_and_handler_impl(
+  extension: String,
+  clear_handler: bool,
+) -> Result<RemoveExtensionResult, PlatformError> {
+  let normalized = validate_removable_extension(&extension)?;
+
+  let mut handler_removed = false;
+  if clear_handler {
+    let tracked = load_extension_list()?;
+    let mut value = load_launch_services_value()?;
+    let handlers = handlers_from_value_mut(&mut value)?;
+    handler_removed = remove_extension_handlers(handlers, slice::from_ref(&normalized), &tracked);
+    // 没有找到对应条目时不重写配置文件，也不必重启 cfprefsd
+    if handler_removed {
+      save_launch_services_value(&value)?;
+      restart_preferences_daemon();
+    }
+  }
+
+  unregister_extension(&normalized)?;
+  Ok(RemoveExtensionResult {
+    associations: list_file_associations_impl()?,
+    handler_removed,
+  })
+}
+
+fn validate_removable_extension(extension: &str) -> Result<String, PlatformError> {
+  let normalized = ensure_extension_normalized(extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  if default_extensions().any(|ext| ext.eq_ignore_ascii_case(&normalized)) {
+    return Err(PlatformError::BuiltinExtension(normalized));
+  }
+
+  Ok(normalized)
+}
+
+fn set_default_application_impl(
+  extension: String,
+  application_path: String,
+  force: bool,
+  role: HandlerRole,
+) -> Result<SetDefaultResult, PlatformError> {
+  // 应用没有声明支持该类型时返回状态而不是错误，由界面询问用户是否强制设置
+  let (normalized, app_path, bundle_id) =
+    match resolve_default_application_target(&extension, &application_path, force) {
+      Ok(target) => target,
+      Err(err @ PlatformError::UnsupportedByApp(..)) => {
+        return Ok(SetDefaultResult {
+          status: ApplyStatus::NeedsConfirmation,
+          detail: Some(err.to_string()),
+          refreshed: false,
+        });
+      }
+      Err(err) => return Err(err),
+    };
+
+  register_extension_if_needed(&normalized)?;
+
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+  let previous_bundle_id = find_role_bundle_id_for_extension(handlers, &normalized, role);
+
+  // 用户重新确认了当前的默认应用时不写文件，也不重启 cfprefsd；
+  // plist 已写入但 LaunchServices 尚未采用时仍需重新设置
+  let live_bundle_id = copy_default_role_handler_for_content_type(
+    &lookup_content_type(&normalized),
+    role_mask(role),
+  );
+  if is_already_default(previous_bundle_id.as_deref(), live_bundle_id.as_deref(), &bundle_id) {
+    return Ok(SetDefaultResult {
+      status: ApplyStatus::NoChange,
+      detail: None,
+      refreshed: false,
+    });
+  }
+
+  upsert_default_application(handlers, &normalized, &bundle_id, role);
+
+  save_launch_services_value(&value)?;
+  let live_failure = apply_live_updates(&[(normalized.clone(), bundle_id.clone(), role)]).pop();
+  let refreshed = restart_preferences_daemon();
+
+  record_change(
+    &normalized,
+    previous_bundle_id,
+    live_bundle_id,
+    &bundle_id,
+    Some(&app_path),
+    role,
+  );
+
+  let mut result = verify_default_application(&normalized, &bundle_id, refreshed, role);
+  if let Some((_, err)) = live_failure {
+    // plist 已写入，LaunchServices 拒绝时把原因告诉界面，而不是只显示等待生效
+    result.detail = Some(err);
+    return Ok(result);
+  }
+  if matches!(result.status, ApplyStatus::PendingRelaunch)
+    && load_settings().refresh_launch_services_on_mismatch
+  {
+    // 可选：处理程序没有立即生效时做一次轻量的 LaunchServices 重建后再检查
+    match rebuild_launch_services_impl(false, |_| {}) {
+      Ok(_) => return Ok(verify_default_application(&normalized, &bundle_id, refreshed, role)),
+      Err(err) => logger::warn(format!("刷新 LaunchServices 数据库失败: {err}")),
+    }
+  }
+
+  Ok(result)
+}
+
+fn is_already_default(
+  plist_bundle_id: Option<&str>,
+  live_bundle_id: Option<&str>,
+  bundle_id: &str,
+) -> bool {
+  let matches =
+    |current: Option<&str>| current.is_some_and(|id| id.eq_ignore_ascii_case(bundle_id));
+  matches(plist_bundle_id) && matches(live_bundle_id)
+}
+
+fn set_default_application_by_bundle_id_impl(
+  extension: String,
+  bundle_id: String,
+  allow_missing: bool,
+) -> Result<FileAssociation, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let bundle_id = bundle_id.trim().to_string();
+  if bundle_id.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "应用 ID 不能为空".into(),
+    ));
+  }
+  // 只用于确认应用已安装，写入时直接使用传入的 bundle id
+  let app_path = match bundle_path_from_id(&bundle_id) {
+    Ok(path) => Some(path),
+    Err(_) if allow_missing => None,
+    Err(_) => return Err(PlatformError::ApplicationNotFound(bundle_id)),
+  };
+
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+  let previous_bundle_id = find_bundle_id_for_extension(handlers, &normalized);
+  // 与 set_default_application_impl 一致：plist 已写入但 LaunchServices 尚未采用时仍需重新设置；
+  // 应用未安装时 LaunchServices 不可能采用，只比较 plist
+  let live_bundle_id =
+    copy_default_role_handler_for_content_type(&lookup_content_type(&normalized), LS_ROLES_ALL);
+  let expected_live = if app_path.is_some() {
+    live_bundle_id.as_deref()
+  } else {
+    previous_bundle_id.as_deref()
+  };
+  let unchanged = is_already_default(previous_bundle_id.as_deref(), expected_live, &bundle_id);
+
+  if !unchanged {
+    register_extension_if_needed(&normalized)?;
+    upsert_default_application(handlers, &normalized, &bundle_id, HandlerRole::All);
+    save_launch_services_value(&value)?;
+    // 应用尚未安装时 LaunchServices 会拒绝该 bundle id，只写入 plist，安装后即可生效
+    if app_path.is_some() {
+      apply_live_updates(&[(normalized.clone(), bundle_id.clone(), HandlerRole::All)]);
+    }
+    restart_preferences_daemon();
+    record_change(
+      &normalized,
+      previous_bundle_id,
+      live_bundle_id,
+      &bundle_id,
+      app_path.as_deref(),
+      HandlerRole::All,
+    );
+  }
 
-  for root in roots {
-    let mut apps = Vec::new();
-    collect_apps(&root, 2, &mut apps);
-    // First, match by CFBundleIdentifier
-    for path in &apps {
-      let info_path = path.join("Contents").join("Info.plist");
-      if let Ok(value) = Value::from_file(&info_path) {
-        if let Some(dict) = value.as_dictionary() {
-          let id = dict.get("CFBundleIdentifier").and_then(Value::as_string);
-          if let Some(id) = id {
-            let a = id.to_ascii_lowercase();
-            let b = bundle_id.to_ascii_lowercase();
-            if a == b || a.ends_with(&b) || b.ends_with(&a) {
-              return Some(path.clone());
-            }
-          }
-        }
+  let handlers = handlers_from_value(&value)?;
+  Ok(resolve_association(handlers, &normalized))
+}
+
+fn load_change_history() -> Result<Vec<ChangeRecord>, PlatformError> {
+  load_change_history_from(&history_path()?)
+}
+
+fn load_change_history_from(path: &Path) -> Result<Vec<ChangeRecord>, PlatformError> {
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+
+  let text = fs::read_to_string(path)?;
+  match serde_json::from_str(&text) {
+    Ok(records) => Ok(records),
+    Err(err) => {
+      // 历史文件损坏时从空记录重新开始，而不是让修改操作失败
+      logger::warn(format!("修改历史文件已损坏，将重新记录: {err}"));
+      Ok(Vec::new())
+    }
+  }
+}
+
+fn record_change(
+  extension: &str,
+  previous_bundle_id: Option<String>,
+  previous_live_bundle_id: Option<String>,
+  bundle_id: &str,
+  application_path: Option<&Path>,
+  role: HandlerRole,
+) {
+  // 历史记录写入失败不影响本次修改，只是之后无法撤销
+  let record = ChangeRecord {
+    timestamp: unix_timestamp(),
+    extension: extension.to_string(),
+    previous_bundle_id,
+    previous_live_bundle_id,
+    bundle_id: bundle_id.to_string(),
+    application_path: application_path
+      .map(|path| path.to_string_lossy().into_owned())
+      .unwrap_or_default(),
+    role,
+  };
+  if let Err(err) = append_change_record(record) {
+    logger::warn(format!("写入修改历史失败: {err}"));
+  }
+}
+
+fn append_change_record(record: ChangeRecord) -> Result<(), PlatformError> {
+  append_change_record_to(&history_path()?, record)
+}
+
+fn append_change_record_to(path: &Path, record: ChangeRecord) -> Result<(), PlatformError> {
+  let mut records = load_change_history_from(path)?;
+  records.push(record);
+  if records.len() > MAX_HISTORY_ENTRIES {
+    records.drain(..records.len() - MAX_HISTORY_ENTRIES);
+  }
+  write_json_store(path, &records)
+}
+
+fn save_change_history(records: &[ChangeRecord]) -> Result<(), PlatformError> {
+  write_json_store(&history_path()?, records)
+}
+
+fn undo_last_change_impl() -> Result<FileAssociation, PlatformError> {
+  let mut records = load_change_history()?;
+  let record = records
+    .pop()
+    .ok_or_else(|| PlatformError::InvalidSelection("没有可以撤销的修改".into()))?;
+
+  let tracked = load_extension_list()?;
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+
+  // 批量、预设、导入、重置等修改不写入历史，处理程序已不是记录中的应用时撤销会覆盖较新的设置
+  let current = find_role_bundle_id_for_extension(handlers, &record.extension, record.role);
+  if !current.is_some_and(|id| id.eq_ignore_ascii_case(&record.bundle_id)) {
+    return Err(PlatformError::InvalidSelection(format!(
+      ".{} 的默认应用在这次修改之后已被其他操作更改，无法撤销",
+      record.extension
+    )));
+  }
+
+  let mut live_updates = Vec::new();
+  match &record.previous_bundle_id {
+    Some(previous) => {
+      // 之前的应用已被卸载时无法恢复，保留历史记录以便用户重新安装后再试
+      bundle_path_from_id(previous)
+        .map_err(|_| PlatformError::ApplicationNotFound(previous.clone()))?;
+      // 按原来的角色恢复，避免撤销查看/编辑角色时把其他角色一起覆盖
+      upsert_default_application(handlers, &record.extension, previous, record.role);
+      live_updates.push((record.extension.clone(), previous.clone(), record.role));
+    }
+    None => {
+      if record.role == HandlerRole::All {
+        remove_extension_handlers(handlers, slice::from_ref(&record.extension), &tracked);
+      } else {
+        remove_role_handler(handlers, &record.extension, record.role);
+      }
+      // 只移除 plist 条目时 LaunchServices 仍在使用新应用，需要把修改前的处理程序设回去
+      if let Some(previous_live) = &record.previous_live_bundle_id {
+        live_updates.push((record.extension.clone(), previous_live.clone(), record.role));
       }
     }
+  }
 
-    // Next, match by app folder name or CFBundleName hint
-    let hint = bundle_id.rsplit('.').next().unwrap_or(bundle_id).to_ascii_lowercase();
-    for path in apps {
-      let stem = path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_ascii_lowercase());
-      if stem.as_deref().map(|s| s.contains(&hint)).unwrap_or(false) {
-        return Some(path);
+  save_launch_services_value(&value)?;
+  apply_live_updates(&live_updates);
+  restart_preferences_daemon();
+  save_change_history(&records)?;
+
+  // 与重置为系统默认一致，以 LaunchServices 在撤销后报告的处理程序为准
+  let live_bundle_id = copy_default_role_handler_for_content_type(
+    &lookup_content_type(&record.extension),
+    role_mask(record.role),
+  );
+  if live_bundle_id.is_some_and(|id| id.eq_ignore_ascii_case(&record.bundle_id)) {
+    return Err(PlatformError::NotApplied(format!(
+      "plist 已恢复，但 LaunchServices 仍让 .{} 使用 {}，重新登录后生效",
+      record.extension, record.bundle_id
+    )));
+  }
+
+  let handlers = handlers_from_value(&value)?;
+  Ok(resolve_association(handlers, &record.extension))
+}
+
+fn get_change_history_impl(limit: usize) -> Result<Vec<ChangeRecord>, PlatformError> {
+  Ok(newest_changes(load_change_history()?, limit))
+}
+
+// 历史文件按时间顺序追加，界面需要最新的在前
+fn newest_changes(records: Vec<ChangeRecord>, limit: usize) -> Vec<ChangeRecord> {
+  records.into_iter().rev().take(limit).collect()
+}
+
+fn resolve_default_application_target(
+  extension: &str,
+  application_path: &str,
+  force: bool,
+) -> Result<(String, PathBuf, String), PlatformError> {
+  let normalized = validate_extension(extension)?;
+  let app_path = resolve_app_bundle_path(application_path)?;
+
+  if !force {
+    if let Some(info_dict) = read_info_dictionary(&app_path) {
+      if !app_supports_extension(&info_dict, &normalized) {
+        return Err(PlatformError::UnsupportedByApp(
+          read_app_display_name(&info_dict, &app_path),
+          normalized,
+        ));
       }
-      let info_path = path.join("Contents").join("Info.plist");
-      if let Ok(value) = Value::from_file(&info_path) {
-        if let Some(dict) = value.as_dictionary() {
-          let name = dict.get("CFBundleName").and_then(Value::as_string);
-          if let Some(name) = name {
-            if name.to_ascii_lowercase().contains(&hint) {
-              return Some(path);
-            }
+    }
+  }
+
+  let bundle_id = registered_bundle_id_from_path(&app_path)?;
+  Ok((normalized, app_path, bundle_id))
+}
+
+fn preview_set_default_application_impl(
+  extension: String,
+  application_path: String,
+  force: bool,
+  role: HandlerRole,
+) -> Result<String, PlatformError> {
+  let (normalized, _, bundle_id) =
+    resolve_default_application_target(&extension, &application_path, force)?;
+
+  let value = load_launch_services_value()?;
+  let before = handlers_from_value(&value)?;
+  let mut after = before.clone();
+
+  // 只在副本上执行与 apply_default_application 相同的 upsert，不写入 plist 也不调用 LaunchServices
+  upsert_default_application(&mut after, &normalized, &bundle_id, role);
+
+  let mut lines = Vec::new();
+  for (index, handler) in after.iter().enumerate() {
+    match before.get(index) {
+      Some(previous) if previous == handler => {}
+      Some(previous) => {
+        lines.push(format!("- {}", describe_handler_entry(previous)));
+        lines.push(format!("+ {}", describe_handler_entry(handler)));
+      }
+      None => lines.push(format!("+ {}", describe_handler_entry(handler))),
+    }
+  }
+
+  if lines.is_empty() {
+    return Ok("LSHandlers 无需修改".into());
+  }
+  Ok(lines.join("\n"))
+}
+
+fn describe_handler_entry(handler: &Value) -> String {
+  let Some(dict) = handler.as_dictionary() else {
+    return format!("{handler:?}");
+  };
+  dict
+    .iter()
+    .map(|(key, value)| match value.as_string() {
+      Some(text) => format!("{key} = {text}"),
+      None => format!("{key} = {value:?}"),
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn set_default_application_for_content_type_impl(
+  content_type: String,
+  application_path: String,
+) -> Result<Vec<String>, PlatformError> {
+  let content_type = content_type.trim();
+  if content_type.is_empty() || !content_type.contains('.') {
+    return Err(PlatformError::InvalidSelection(format!(
+      "内容类型格式无效: {content_type}"
+    )));
+  }
+  validate_content_type_chars(content_type)?;
+
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let bundle_id = registered_bundle_id_from_path(&app_path)?;
+
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+  upsert_content_type_handler(handlers, content_type, &bundle_id, HandlerRole::All);
+
+  // 与其他修改一致：plist 保存成功后再更新 LaunchServices
+  save_launch_services_value(&value)?;
+  let live_result = set_launchservices_default(content_type, &bundle_id, HandlerRole::All);
+  restart_preferences_daemon();
+  live_result?;
+
+  Ok(extensions_for_content_type(content_type))
+}
+
+fn extensions_for_content_type(content_type: &str) -> Vec<String> {
+  // 同时考虑用户在 content_types.json 中的覆盖，结果按字母顺序排列
+  let mut candidates: BTreeSet<String> = EXTENSION_TO_CONTENT_TYPE
+    .iter()
+    .map(|(ext, _)| ext.to_string())
+    .collect();
+  if let Ok(overrides) = user_content_types() {
+    candidates.extend(overrides.into_keys());
+  }
+
+  candidates
+    .into_iter()
+    .filter(|ext| {
+      extension_to_content_type(ext)
+        .map(|uti| uti.eq_ignore_ascii_case(content_type))
+        .unwrap_or(false)
+    })
+    .collect()
+}
+
+fn detect_conflicts_impl() -> Result<Vec<Conflict>, PlatformError> {
+  // 共享同一内容类型的扩展名无法分别设置默认应用，修改其中一个会影响其他扩展名
+  let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+  for extension in load_extension_list()? {
+    if let Some(content_type) = extension_to_content_type(&extension) {
+      groups.entry(content_type).or_default().push(extension);
+    }
+  }
+
+  Ok(
+    groups
+      .into_iter()
+      .filter(|(_, extensions)| extensions.len() > 1)
+      .map(|(content_type, extensions)| Conflict {
+        content_type,
+        extensions,
+      })
+      .collect(),
+  )
+}
+
+fn verify_default_application(
+  extension: &str,
+  bundle_id: &str,
+  refreshed: bool,
+  role: HandlerRole,
+) -> SetDefaultResult {
+  // LaunchServices 可能仍返回旧的处理程序，直到相关应用重新启动或重新登录
+  let content_type = lookup_content_type(extension);
+  match copy_default_role_handler_for_content_type(&content_type, role_mask(role)) {
+    Some(current) if current.eq_ignore_ascii_case(bundle_id) => SetDefaultResult {
+      status: ApplyStatus::Applied,
+      detail: None,
+      refreshed,
+    },
+    current => SetDefaultResult {
+      status: ApplyStatus::PendingRelaunch,
+      detail: Some(format!(
+        "{content_type} 当前的处理程序为 {}，预期为 {bundle_id}",
+        current.as_deref().unwrap_or("(无)")
+      )),
+      refreshed,
+    },
+  }
+}
+
+fn diagnose_extension_impl(extension: String) -> ExtensionDiagnosis {
+  let mut diagnosis = ExtensionDiagnosis {
+    extension: extension.clone(),
+    ..Default::default()
+  };
+
+  let normalized = match validate_extension(&extension) {
+    Ok(normalized) => normalized,
+    Err(err) => {
+      diagnosis.errors.push(format!("规范化: {err}"));
+      return diagnosis;
+    }
+  };
+  diagnosis.normalized = Some(normalized.clone());
+
+  diagnosis.content_type = resolved_content_type(&normalized);
+  if diagnosis.content_type.is_none() {
+    diagnosis.errors.push("内容类型: 内置表和系统都没有给出该扩展名的正式 UTI".into());
+  }
+
+  match load_launch_services_value().and_then(|value| {
+    let handlers = handlers_from_value(&value)?;
+    Ok((
+      has_handler_entry(handlers, &normalized),
+      find_bundle_id_for_extension(handlers, &normalized),
+    ))
+  }) {
+    Ok((found, bundle_id)) => {
+      diagnosis.handler_found = found;
+      diagnosis.handler_bundle_id = bundle_id;
+    }
+    Err(err) => diagnosis.errors.push(format!("LSHandlers: {err}")),
+  }
+
+  diagnosis.system_default_bundle_id = system_default_bundle_id_for_extension(&normalized);
+
+  // 与 resolve_association 的顺序一致：先用 LaunchServices 实际会打开的应用，
+  // 取不到时才按用户设置、系统默认的顺序查找路径
+  let live_path = default_application_path_for_content_type(&lookup_content_type(&normalized));
+  if let Some(path) = live_path {
+    diagnosis.application_path = Some(path.display().to_string());
+    return diagnosis;
+  }
+
+  let bundle_id = diagnosis
+    .handler_bundle_id
+    .clone()
+    .or_else(|| diagnosis.system_default_bundle_id.clone());
+  match bundle_id {
+    Some(bundle_id) => match bundle_path_from_id(&bundle_id) {
+      Ok(path) => diagnosis.application_path = Some(path.display().to_string()),
+      Err(err) => diagnosis.errors.push(format!("应用路径 ({bundle_id}): {err}")),
+    },
+    None => diagnosis.errors.push("默认应用: 没有找到任何处理程序".into()),
+  }
+
+  diagnosis
+}
+
+fn verify_association_impl(extension: String) -> Result<AssociationVerification, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let value = load_launch_services_value()?;
+  let handlers = handlers_from_value(&value)?;
+
+  let content_type = lookup_content_type(&normalized);
+  let expected_bundle_id = find_bundle_id_for_extension(handlers, &normalized);
+  let actual_bundle_id = copy_default_handler_for_content_type(&content_type);
+  // 没有自定义记录时由系统决定，不存在“未生效”的情况
+  let effective = match (&expected_bundle_id, &actual_bundle_id) {
+    (None, _) => true,
+    (Some(expected), Some(actual)) => expected.eq_ignore_ascii_case(actual),
+    (Some(_), None) => false,
+  };
+
+  Ok(AssociationVerification {
+    extension: normalized,
+    content_type,
+    expected_bundle_id,
+    actual_bundle_id,
+    effective,
+  })
+}
+
+fn set_default_application_for_extensions_impl(
+  extensions: Vec<String>,
+  application_path: String,
+) -> Result<BatchSetResult, PlatformError> {
+  apply_default_application_batch(extensions, &application_path, false)
+}
+
+fn set_default_application_for_category_impl(
+  category: String,
+  application_path: String,
+) -> Result<BatchSetResult, PlatformError> {
+  let category = category.trim();
+  let extensions: Vec<String> = load_extension_list()?
+    .into_iter()
+    .filter(|ext| category_for_extension(ext).eq_ignore_ascii_case(category))
+    .collect();
+  if extensions.is_empty() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "分类 {category} 中没有扩展名"
+    )));
+  }
+
+  // 分类中常混有应用不支持的类型（例如把图片查看器设给整个 Media 分类），逐个检查后跳过
+  apply_default_application_batch(extensions, &application_path, true)
+}
+
+fn apply_profile_impl(
+  profile_name: String,
+  app_overrides: HashMap<String, String>,
+) -> Result<BatchSetResult, PlatformError> {
+  let profile = builtin_profiles()
+    .into_iter()
+    .find(|profile| profile.name.eq_ignore_ascii_case(profile_name.trim()))
+    .ok_or_else(|| PlatformError::InvalidSelection(format!("未知的预设: {profile_name}")))?;
+
+  // 用户没有为其选择应用的分组保持不变
+  let groups: Vec<(Vec<String>, &str)> = profile
+    .groups
+    .into_iter()
+    .filter_map(|group| {
+      let path = app_overrides.get(&group.key)?.as_str();
+      Some((group.extensions, path))
+    })
+    .collect();
+  if groups.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "请至少为一个分组选择应用".into(),
+    ));
+  }
+
+  apply_default_application_groups(groups, false)
+}
+
+const SAVED_PROFILE_SUFFIX: &str = ".profile.json";
+
+// 预设与导出文件共用 profiles 目录，用后缀区分
+fn saved_profile_path(name: &str) -> Result<(String, PathBuf), PlatformError> {
+  let name = name.trim();
+  if name.is_empty() || name.starts_with('.') || name.contains(['/', ':', '\\']) {
+    return Err(PlatformError::InvalidSelection(format!(
+      "无效的预设名称: {name}"
+    )));
+  }
+  let path = profiles_dir_path()?.join(format!("{name}{SAVED_PROFILE_SUFFIX}"));
+  Ok((name.to_string(), path))
+}
+
+fn read_saved_profile(path: &Path) -> Result<AssociationExport, PlatformError> {
+  let text = fs::read_to_string(path)?;
+  serde_json::from_str(&text).map_err(|err| PlatformError::Config(err.to_string()))
+}
+
+fn saved_profile_summary(name: String, path: &Path) -> Option<SavedProfile> {
+  let export = read_saved_profile(path).ok()?;
+  let created_at = fs::metadata(path)
+    .ok()?
+    .modified()
+    .ok()?
+    .duration_since(UNIX_EPOCH)
+    .ok()?
+    .as_secs();
+  Some(SavedProfile {
+    name,
+    path: path.display().to_string(),
+    created_at,
+    association_count: export.associations.len(),
+  })
+}
+
+fn save_profile_impl(name: String) -> Result<SavedProfile, PlatformError> {
+  let (name, path) = saved_profile_path(&name)?;
+  // 与导出使用相同的格式，保存时覆盖同名预设
+  let export = build_association_export()?;
+  write_json_store(&path, &export)?;
+
+  saved_profile_summary(name, &path)
+    .ok_or_else(|| PlatformError::Config("预设保存后无法读取".into()))
+}
+
+fn list_profiles_impl() -> Result<Vec<SavedProfile>, PlatformError> {
+  let dir = profiles_dir_path()?;
+  if !dir.exists() {
+    return Ok(Vec::new());
+  }
+
+  let mut profiles: Vec<SavedProfile> = fs::read_dir(&dir)?
+    .flatten()
+    .filter_map(|entry| {
+      let file_name = entry.file_name().to_string_lossy().into_owned();
+      let name = file_name.strip_suffix(SAVED_PROFILE_SUFFIX)?.to_string();
+      saved_profile_summary(name, &entry.path())
+    })
+    .collect();
+
+  profiles.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(profiles)
+}
+
+fn apply_saved_profile_impl(name: String) -> Result<ImportReport, PlatformError> {
+  let (name, path) = saved_profile_path(&name)?;
+  if !path.is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "预设不存在: {name}"
+    )));
+  }
+
+  // 已卸载的应用会出现在 skipped 中，不影响其余条目
+  import_association_export(read_saved_profile(&path)?, false)
+}
+
+fn delete_profile_impl(name: String) -> Result<Vec<SavedProfile>, PlatformError> {
+  let (name, path) = saved_profile_path(&name)?;
+  if !path.is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "预设不存在: {name}"
+    )));
+  }
+
+  fs::remove_file(&path)?;
+  list_profiles_impl()
+}
+
+fn apply_default_application_batch(
+  extensions: Vec<String>,
+  application_path: &str,
+  check_support: bool,
+) -> Result<BatchSetResult, PlatformError> {
+  apply_default_application_groups(vec![(extensions, application_path)], check_support)
+}
+
+fn apply_default_application_groups(
+  groups: Vec<(Vec<String>, &str)>,
+  check_support: bool,
+) -> Result<BatchSetResult, PlatformError> {
+  // 先解析所有分组的应用，任何一个路径无效都在修改 LaunchServices 之前返回错误
+  let mut targets = Vec::with_capacity(groups.len());
+  for (extensions, application_path) in groups {
+    let app_path = resolve_app_bundle_path(application_path)?;
+    let bundle_id = registered_bundle_id_from_path(&app_path)?;
+    let info_dict = if check_support {
+      read_info_dictionary(&app_path)
+    } else {
+      None
+    };
+    targets.push((extensions, app_path, bundle_id, info_dict));
+  }
+
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+
+  // 单个扩展名失败不影响其他扩展名，最后统一写入 plist 并只重启一次 cfprefsd
+  let mut applied = Vec::new();
+  let mut results = Vec::new();
+  let mut live_updates = Vec::new();
+  for (extensions, app_path, bundle_id, info_dict) in targets {
+    for extension in extensions {
+      let outcome = validate_extension(&extension).and_then(|normalized| {
+        if let Some(info_dict) = &info_dict {
+          if !app_supports_extension(info_dict, &normalized) {
+            return Err(PlatformError::UnsupportedByApp(
+              read_app_display_name(info_dict, &app_path),
+              normalized,
+            ));
           }
         }
+        upsert_default_application(handlers, &normalized, &bundle_id, HandlerRole::All);
+        live_updates.push((normalized.clone(), bundle_id.clone(), HandlerRole::All));
+        Ok(normalized)
+      });
+
+      match outcome {
+        Ok(normalized) => {
+          applied.push(normalized.clone());
+          results.push(ExtensionOutcome {
+            extension: normalized,
+            applied: true,
+            error: None,
+          });
+        }
+        Err(err) => results.push(ExtensionOutcome {
+          extension,
+          applied: false,
+          error: Some(err.to_string()),
+        }),
+      }
+    }
+  }
+
+  if !applied.is_empty() {
+    register_extensions_if_needed(&applied)?;
+    save_launch_services_value(&value)?;
+    for (extension, err) in apply_live_updates(&live_updates) {
+      if let Some(outcome) = results.iter_mut().find(|outcome| outcome.extension == extension) {
+        outcome.error = Some(err);
       }
     }
+    restart_preferences_daemon();
+  }
+
+  Ok(BatchSetResult {
+    associations: list_file_associations_impl()?,
+    results,
+  })
+}
+
+// 必须在 plist 保存成功之后调用，保存失败时 LaunchServices 保持原样，两者不会不一致；
+// 返回更新失败的扩展名和原因
+fn apply_live_updates(updates: &[(String, String, HandlerRole)]) -> Vec<(String, String)> {
+  let mut failures = Vec::new();
+  for (extension, bundle_id, role) in updates {
+    if let Err(err) = set_live_default_application(extension, bundle_id, *role) {
+      logger::warn(format!("更新 .{extension} 的 LaunchServices 默认应用失败: {err}"));
+      failures.push((extension.clone(), err.to_string()));
+    }
+  }
+  failures
+}
+
+fn set_live_default_application(
+  extension: &str,
+  bundle_id: &str,
+  role: HandlerRole,
+) -> Result<(), PlatformError> {
+  match resolved_content_type(extension) {
+    Some(content_type) => set_launchservices_default(&content_type, bundle_id, role),
+    // 系统也只能给出 dyn. 动态类型时，改为按扩展名标签设置
+    None => set_extension_handler_by_tag(extension, bundle_id, role),
+  }
+}
+
+// 只修改 plist 中的条目，不调用 LaunchServices
+fn upsert_default_application(
+  handlers: &mut Vec<Value>,
+  extension: &str,
+  bundle_id: &str,
+  role: HandlerRole,
+) {
+  if let Some(content_type) = resolved_content_type(extension) {
+    upsert_content_type_handler(handlers, &content_type, bundle_id, role);
+  }
+  upsert_extension_handler(handlers, extension, bundle_id, role);
+}
+
+fn role_plist_key(role: HandlerRole) -> &'static str {
+  match role {
+    HandlerRole::All => "LSHandlerRoleAll",
+    HandlerRole::Viewer => "LSHandlerRoleViewer",
+    HandlerRole::Editor => "LSHandlerRoleEditor",
+  }
+}
+
+fn role_mask(role: HandlerRole) -> u32 {
+  match role {
+    HandlerRole::All => LS_ROLES_ALL,
+    HandlerRole::Viewer => LS_ROLES_VIEWER,
+    HandlerRole::Editor => LS_ROLES_EDITOR,
   }
-  None
 }
 
-fn collect_apps(root: &Path, depth: usize, acc: &mut Vec<PathBuf>) {
-  if depth == 0 {
-    return;
+// 设置 LSHandlerRoleAll 时一并移除单独的查看/编辑角色，否则它们仍会指向旧的应用
+fn set_role_handler(dict: &mut Dictionary, bundle_id: &str, role: HandlerRole) {
+  if role == HandlerRole::All {
+    dict.remove("LSHandlerRoleViewer");
+    dict.remove("LSHandlerRoleEditor");
   }
-  if let Ok(read_dir) = fs::read_dir(root) {
-    for entry in read_dir.flatten() {
-      let path = entry.path();
-      if path.extension().map(|e| e.eq_ignore_ascii_case("app")).unwrap_or(false) {
-        acc.push(path);
-      } else if path.is_dir() {
-        collect_apps(&path, depth - 1, acc);
-      }
+  dict.insert(
+    role_plist_key(role).to_string(),
+    Value::String(bundle_id.to_string()),
+  );
+}
+
+// 只移除扩展名相关条目中的单个角色，条目不再包含任何角色时整条删除
+fn remove_role_handler(handlers: &mut Vec<Value>, extension: &str, role: HandlerRole) -> bool {
+  let content_type = resolved_content_type(extension);
+  let mut changed = false;
+  handlers.retain_mut(|handler| {
+    let Some(dict) = handler.as_dictionary_mut() else {
+      return true;
+    };
+    let matches_extension = dict
+      .get("LSHandlerContentTag")
+      .and_then(Value::as_string)
+      .is_some_and(|tag| tag.eq_ignore_ascii_case(extension))
+      && dict.get("LSHandlerContentTagClass").and_then(Value::as_string)
+        == Some("public.filename-extension");
+    let matches_content_type = content_type.is_some()
+      && dict.get("LSHandlerContentType").and_then(Value::as_string) == content_type.as_deref();
+    if !(matches_extension || matches_content_type) || dict.remove(role_plist_key(role)).is_none()
+    {
+      return true;
+    }
+    changed = true;
+    ["LSHandlerRoleAll", "LSHandlerRoleViewer", "LSHandlerRoleEditor"]
+      .iter()
+      .any(|key| dict.contains_key(key))
+  });
+  changed
+}
+
+// plist 中为该扩展名实际记录的各个角色，查看角色不会像 find_bundle_id_for_extension 那样被当作 all
+fn recorded_role_handlers(handlers: &[Value], extension: &str) -> Vec<(HandlerRole, String)> {
+  [HandlerRole::All, HandlerRole::Viewer, HandlerRole::Editor]
+    .into_iter()
+    .filter_map(|role| {
+      handler_entries_for_extension(handlers, extension)
+        .find_map(|dict| dict.get(role_plist_key(role)).and_then(Value::as_string))
+        .map(|bundle_id| (role, bundle_id.to_string()))
+    })
+    .collect()
+}
+
+fn split_role_handlers(handlers: &[Value], extension: &str) -> (Option<String>, Option<String>) {
+  (
+    find_role_bundle_id_for_extension(handlers, extension, HandlerRole::Viewer),
+    find_role_bundle_id_for_extension(handlers, extension, HandlerRole::Editor),
+  )
+}
+
+fn reset_extension_to_system_default_impl(
+  extension: String,
+) -> Result<FileAssociation, PlatformError> {
+  let normalized = ensure_extension_normalized(&extension);
+
+  if normalized.is_empty() {
+    return Err(PlatformError::InvalidSelection(
+      "扩展名不能为空".into(),
+    ));
+  }
+
+  let tracked = load_extension_list()?;
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+
+  // 没有可移除的条目时已经是系统默认，不写文件，也不重启 cfprefsd
+  if !remove_extension_handlers(handlers, slice::from_ref(&normalized), &tracked) {
+    return Ok(resolve_association(handlers, &normalized));
+  }
+
+  save_launch_services_value(&value)?;
+  restart_preferences_daemon();
+
+  // 以 LaunchServices 在重置后报告的处理程序为准，而不是内存中的 plist
+  let handlers = handlers_from_value(&value)?;
+  let mut association = resolve_association(handlers, &normalized);
+  let Some(bundle_id) = system_default_bundle_id_for_extension(&normalized) else {
+    return Ok(association);
+  };
+  match bundle_path_from_id(&bundle_id) {
+    Ok(path) => {
+      association.application_name =
+        application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone());
+      association.application_path = path.display().to_string();
+      association.status = AssociationStatus::Resolved;
+    }
+    Err(_) => {
+      association.application_name = humanize_bundle_id(&bundle_id);
+      association.application_path = String::new();
+      association.status = AssociationStatus::AppNotFound;
     }
   }
+  association.bundle_identifier = bundle_id;
+  Ok(association)
 }
 
-fn read_app_display_name(info_dict: &Dictionary, fallback: &Path) -> String {
-  // 优先使用 Info.plist 中的显示名称，其次使用 CFBundleName，最后退回到包文件夹名
-  if let Some(name) = info_dict
-    .get("CFBundleDisplayName")
-    .and_then(Value::as_string)
-    .map(|s| s.to_string())
-  {
-    return name;
+fn reset_all_to_system_defaults_impl(
+  clear_extensions: bool,
+) -> Result<Vec<FileAssociation>, PlatformError> {
+  let tracked = load_extension_list()?;
+  let mut value = load_launch_services_value()?;
+  let handlers = handlers_from_value_mut(&mut value)?;
+
+  // 一次性处理全部扩展名，共享同一 UTI 的内容类型条目也会随之移除
+  let changed = remove_extension_handlers(handlers, &tracked, &tracked);
+
+  // save_launch_services_value 会先备份原文件，与单个修改一致
+  if changed {
+    save_launch_services_value(&value)?;
+    restart_preferences_daemon();
   }
 
-  if let Some(name) = info_dict
-    .get("CFBundleName")
-    .and_then(Value::as_string)
-    .map(|s| s.to_string())
-  {
-    return name;
+  if clear_extensions {
+    let defaults: Vec<String> = default_extensions().map(str::to_string).collect();
+    save_extension_list(&defaults)?;
   }
 
-  fallback
-    .file_stem()
-    .and_then(|stem| stem.to_str())
-    .unwrap_or("未知应用")
-    .to_string()
+  list_file_associations_impl()
 }
 
-fn application_name_from_path(app_path: &Path) -> Result<String, PlatformError> {
-  // Prefer Info.plist values; fallback to Spotlight display name; finally use folder name
-  let info_path = app_path.join("Contents").join("Info.plist");
-  match Value::from_file(&info_path) {
-    Ok(info_value) => {
-      if let Some(dict) = info_value.as_dictionary() {
-        return Ok(read_app_display_name(dict, app_path));
-      }
+fn remove_extension_handlers(
+  handlers: &mut Vec<Value>,
+  extensions: &[String],
+  tracked: &[String],
+) -> bool {
+  // 扩展名条目和内容类型条目可能同时存在，需要一起移除
+  let mut removed = false;
+  for extension in extensions {
+    removed |= remove_extension_handler(handlers, extension);
+  }
+
+  // 内容类型条目由共享同一 UTI 的扩展名共用（如 jpg/jpeg），其他仍被跟踪的扩展名还依赖它时只移除扩展名条目
+  let content_types: BTreeSet<String> =
+    extensions.iter().filter_map(|extension| resolved_content_type(extension)).collect();
+  for content_type in content_types {
+    let shared = tracked
+      .iter()
+      .filter(|other| !extensions.contains(other))
+      .any(|other| {
+        resolved_content_type(other).as_deref() == Some(content_type.as_str())
+          && has_handler_entry(handlers, other)
+      });
+    if !shared {
+      removed |= remove_content_type_handler(handlers, &content_type);
     }
-    Err(_) => {}
   }
+  removed
+}
 
-  // Fallback via mdls metadata
-  if let Some(name) = mdls_display_name(app_path) {
-    return Ok(name);
+fn save_launch_services_value(value: &Value) -> Result<(), PlatformError> {
+  let path = launch_services_plist_path()?;
+  backup_launch_services_plist()?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
   }
 
-  Ok(
-    app_path
-      .file_stem()
-      .and_then(|s| s.to_str())
-      .unwrap_or("未知应用")
-      .to_string(),
-  )
+  // 先写入临时文件再替换，避免写到一半时留下损坏的 plist
+  let temp_path = path.with_extension("plist.tmp");
+  plist::to_file_xml(&temp_path, value)?;
+  fs::rename(&temp_path, &path)?;
+  if let Ok(mut last) = LAST_OWN_LAUNCH_SERVICES_WRITE.lock() {
+    *last = Some((SystemTime::now(), value.clone()));
+  }
+  Ok(())
 }
 
-fn mdls_display_name(app_path: &Path) -> Option<String> {
-  let output = Command::new("mdls")
-    .arg("-name")
-    .arg("kMDItemDisplayName")
-    .arg("-raw")
-    .arg(app_path)
-    .output()
-    .ok()?;
-
-  if !output.status.success() {
-    return None;
+fn backup_launch_services_plist() -> Result<Option<PathBuf>, PlatformError> {
+  let source = launch_services_plist_path()?;
+  if !source.exists() {
+    return Ok(None);
   }
-  let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-  if text.is_empty() || text == "(null)" {
-    None
-  } else {
-    Some(text)
+
+  let dir = backups_dir_path()?;
+  fs::create_dir_all(&dir)?;
+  // 同一毫秒内多次写入时追加序号，不能覆盖修改前唯一的副本
+  let stamp = unix_timestamp_millis();
+  let mut sequence = 0;
+  let mut target = dir.join(backup_file_name(stamp, sequence));
+  while target.exists() {
+    sequence += 1;
+    target = dir.join(backup_file_name(stamp, sequence));
   }
+  fs::copy(&source, &target)?;
+  prune_launch_services_backups(&dir)?;
+  Ok(Some(target))
 }
 
-fn bundle_id_from_path(app_path: &Path) -> Result<String, PlatformError> {
-  let info_path = app_path.join("Contents").join("Info.plist");
-  let info_value = Value::from_file(&info_path)?;
-  let dict = info_value
-    .as_dictionary()
-    .ok_or_else(|| PlatformError::MissingInfo("Info.plist 结构无效".into()))?;
+fn prune_launch_services_backups(dir: &Path) -> Result<(), PlatformError> {
+  let mut backups: Vec<((u64, u32), PathBuf)> = fs::read_dir(dir)?
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.path();
+      let order = backup_order(&path)?;
+      Some((order, path))
+    })
+    .collect();
 
-  dict
-    .get("CFBundleIdentifier")
-    .and_then(Value::as_string)
-    .map(|s| s.to_string())
-    .ok_or_else(|| PlatformError::MissingInfo("缺少 CFBundleIdentifier".into()))
+  // 按时间倒序，只保留最近的若干份
+  backups.sort_by_key(|(order, _)| std::cmp::Reverse(*order));
+  for (_, path) in backups.into_iter().skip(MAX_LAUNCH_SERVICES_BACKUPS) {
+    fs::remove_file(path)?;
+  }
+  Ok(())
 }
 
-fn ensure_extension_normalized(ext: &str) -> String {
-  ext.trim_start_matches('.').to_lowercase()
+fn backup_file_name(stamp: u64, sequence: u32) -> String {
+  if sequence == 0 {
+    format!("{LAUNCH_SERVICES_BACKUP_PREFIX}{stamp}{LAUNCH_SERVICES_BACKUP_SUFFIX}")
+  } else {
+    format!("{LAUNCH_SERVICES_BACKUP_PREFIX}{stamp}-{sequence}{LAUNCH_SERVICES_BACKUP_SUFFIX}")
+  }
 }
 
-fn list_file_associations_impl() -> Result<Vec<FileAssociation>, PlatformError> {
-  let value = load_launch_services_value()?;
-  let handlers = handlers_from_value(&value)?;
+// 返回 (毫秒时间戳, 序号)，用于排序和校验备份文件名
+fn backup_order(path: &Path) -> Option<(u64, u32)> {
+  let stem = path
+    .file_name()?
+    .to_str()?
+    .strip_prefix(LAUNCH_SERVICES_BACKUP_PREFIX)?
+    .strip_suffix(LAUNCH_SERVICES_BACKUP_SUFFIX)?;
+  let (stamp, sequence) = match stem.split_once('-') {
+    Some((stamp, sequence)) => (stamp, sequence.parse().ok()?),
+    None => (stem, 0),
+  };
+  let stamp: u64 = stamp.parse().ok()?;
+  // 早期版本的备份以秒命名
+  let millis = if stamp < LEGACY_BACKUP_SECONDS_LIMIT {
+    stamp * 1000
+  } else {
+    stamp
+  };
+  Some((millis, sequence))
+}
 
-  let extensions = load_extension_list()?;
+fn backup_launch_services_impl() -> Result<String, PlatformError> {
+  match backup_launch_services_plist()? {
+    Some(path) => Ok(path.display().to_string()),
+    None => Err(PlatformError::Config(
+      "LaunchServices 配置文件不存在，无需备份".into(),
+    )),
+  }
+}
 
-  let mut results = Vec::with_capacity(extensions.len());
-  for ext in extensions {
-    if let Some(bundle_id) = find_bundle_id_for_extension(handlers, &ext) {
-      match bundle_path_from_id(&bundle_id) {
-        Ok(path) => {
-          let display_name = application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone());
-          results.push(FileAssociation {
-            extension: ext.clone(),
-            application_name: display_name,
-            application_path: path.display().to_string(),
-          });
-        }
-        Err(err) => {
-          results.push(FileAssociation {
-            extension: ext.clone(),
-            application_name: format!("{} (未找到路径)", humanize_bundle_id(&bundle_id)),
-            application_path: err.to_string(),
-          });
-        }
-      }
-    } else {
-      // 尝试通过 LaunchServices 的系统默认关联获取 bundle id
-      if let Some(bundle_id) = system_default_bundle_id_for_extension(&ext) {
-        match bundle_path_from_id(&bundle_id) {
-          Ok(path) => {
-            let display_name =
-              application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone());
-            results.push(FileAssociation {
-              extension: ext.clone(),
-              application_name: display_name,
-              application_path: path.display().to_string(),
-            });
-          }
-          Err(_) => {
-            results.push(FileAssociation {
-              extension: ext.clone(),
-              application_name: humanize_bundle_id(&bundle_id),
-              application_path: String::new(),
-            });
-          }
-        }
-      } else {
-        results.push(FileAssociation {
-          extension: ext.clone(),
-          application_name: "未设置默认应用".into(),
-          application_path: "".into(),
-        });
-      }
-    }
+fn list_launch_services_backups_impl() -> Result<Vec<LaunchServicesBackup>, PlatformError> {
+  let dir = backups_dir_path()?;
+  if !dir.exists() {
+    return Ok(Vec::new());
   }
 
-  Ok(results)
-}
+  let mut backups: Vec<((u64, u32), LaunchServicesBackup)> = fs::read_dir(&dir)?
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.path();
+      let order = backup_order(&path)?;
+      let backup = LaunchServicesBackup {
+        name: entry.file_name().to_string_lossy().into_owned(),
+        path: path.display().to_string(),
+        created_at: order.0 / 1000,
+      };
+      Some((order, backup))
+    })
+    .collect();
 
-fn add_extension_impl(extension: String) -> Result<Vec<FileAssociation>, PlatformError> {
-  let normalized = ensure_extension_normalized(&extension);
+  backups.sort_by_key(|(order, _)| std::cmp::Reverse(*order));
+  Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+}
 
-  if normalized.is_empty() {
-    return Err(PlatformError::InvalidSelection(
-      "扩展名不能为空".into(),
-    ));
+fn restore_launch_services_backup_impl(
+  backup_name: String,
+) -> Result<Vec<FileAssociation>, PlatformError> {
+  let name = backup_name.trim();
+  // 只接受备份目录中的文件名，避免被当作任意路径使用
+  if name.contains('/') || backup_order(Path::new(name)).is_none() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "无效的备份名称: {name}"
+    )));
   }
 
-  if !normalized
-    .chars()
-    .all(|ch| ch.is_ascii_alphanumeric() || ch == '+' || ch == '-')
-  {
-    return Err(PlatformError::InvalidSelection(
-      "扩展名只能包含字母、数字、加号或减号".into(),
-    ));
+  let backup = backups_dir_path()?.join(name);
+  if !backup.is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "备份文件不存在: {}",
+      backup.display()
+    )));
   }
 
-  register_extension_if_needed(&normalized)?;
+  // 先确认备份内容完整，再覆盖当前配置
+  let value = Value::from_file(&backup)?;
+  handlers_from_value(&value)?;
+
+  // 与其他修改一样经由 save_launch_services_value 写入：先备份当前文件（恢复本身也可以撤销），
+  // 原子替换，并记录为本应用的写入，监视线程不会把它当作外部修改
+  save_launch_services_value(&value)?;
+  restart_preferences_daemon();
   list_file_associations_impl()
 }
 
-fn set_default_application_impl(
-  extension: String,
+fn open_with_impl(file_path: String, application_path: String) -> Result<(), PlatformError> {
+  let app_path = resolve_app_bundle_path(&application_path)?;
+  let file = resolve_existing_file(&file_path)?;
+  open_file_in_app(&file, &app_path)
+}
+
+fn open_file_with_application_impl(
+  file_path: String,
   application_path: String,
 ) -> Result<(), PlatformError> {
-  let normalized = ensure_extension_normalized(&extension);
   let app_path = resolve_app_bundle_path(&application_path)?;
+  let file = resolve_existing_file(&file_path)?;
 
-  let bundle_id = bundle_id_from_path(&app_path)?;
-  let content_type = extension_to_content_type(&normalized);
+  // 与 open_with 相同，只是先检查应用是否声明支持该类型，便于界面在设为默认之前提醒用户
+  let extension = file
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(ensure_extension_normalized);
+  if let (Some(extension), Some(info_dict)) = (extension, read_info_dictionary(&app_path)) {
+    if !app_supports_extension(&info_dict, &extension) {
+      return Err(PlatformError::UnsupportedByApp(
+        read_app_display_name(&info_dict, &app_path),
+        extension,
+      ));
+    }
+  }
 
-  register_extension_if_needed(&normalized)?;
+  open_with_impl(file_path, application_path)
+}
 
-  let mut value = load_launch_services_value()?;
-  let handlers = handlers_from_value_mut(&mut value)?;
+fn content_type_for_path_impl(file_path: String) -> Result<String, PlatformError> {
+  let file = resolve_existing_file(&file_path)?;
+
+  // Spotlight 记录的类型最准确；未被索引的文件再根据扩展名向系统查询
+  if let Some(content_type) = mdls_content_type(&file) {
+    return Ok(content_type);
+  }
+
+  file
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .and_then(preferred_content_type_for_extension)
+    .ok_or_else(|| {
+      PlatformError::MissingInfo(format!("无法确定文件类型: {}", file.display()))
+    })
+}
+
+fn inspect_file_impl(file_path: String) -> Result<FileInspection, PlatformError> {
+  let file = resolve_existing_file(&file_path)?;
+
+  if let Some(extension) = extension_for_file(&file) {
+    let is_managed = load_extension_list()?.contains(&extension);
+    let value = load_launch_services_value()?;
+    let handlers = handlers_from_value(&value)?;
+    let association = resolve_association(handlers, &extension);
+    return Ok(FileInspection {
+      content_type: lookup_content_type(&extension),
+      extension: Some(extension),
+      application_name: association.application_name,
+      application_path: association.application_path,
+      is_managed,
+    });
+  }
+
+  // 没有扩展名的文件无法加入列表，改用 Spotlight 记录的内容类型查询处理程序
+  let content_type = mdls_content_type(&file).ok_or_else(|| {
+    PlatformError::MissingInfo(format!("无法确定文件类型: {}", file.display()))
+  })?;
+  let (application_name, application_path) =
+    match copy_default_handler_for_content_type(&content_type) {
+      None => ("未设置默认应用".to_string(), String::new()),
+      Some(bundle_id) => match bundle_path_from_id(&bundle_id) {
+        Ok(path) => (
+          application_name_from_path(&path).unwrap_or_else(|_| bundle_id.clone()),
+          path.display().to_string(),
+        ),
+        Err(_) => (humanize_bundle_id(&bundle_id), String::new()),
+      },
+    };
+
+  Ok(FileInspection {
+    extension: None,
+    content_type,
+    application_name,
+    application_path,
+    is_managed: false,
+  })
+}
+
+fn extension_for_file(file: &Path) -> Option<String> {
+  if let Some(extension) = file.extension().and_then(|ext| ext.to_str()) {
+    return validate_extension(extension).ok();
+  }
 
-  upsert_extension_handler(handlers, &normalized, &bundle_id);
-  if let Some(content_type) = content_type {
-    upsert_content_type_handler(handlers, content_type, &bundle_id);
-    set_launchservices_default(content_type, &bundle_id)?;
+  // .gitignore、.env 这类点开头的文件以及 Dockerfile 在内置列表中按文件名登记
+  let name = file.file_name()?.to_str()?;
+  let normalized = ensure_extension_normalized(name);
+  if name.starts_with('.') || default_extensions().any(|ext| ext == normalized) {
+    validate_extension(&normalized).ok()
   } else {
-    // 对于没有预定义内容类型的扩展名，尝试使用UTTypeCreatePreferredIdentifierForTag
-    set_extension_handler_by_tag(&normalized, &bundle_id)?;
+    None
+  }
+}
+
+fn mdls_content_type(path: &Path) -> Option<String> {
+  let output = output_with_timeout(
+    Command::new("mdls")
+      .arg("-name")
+      .arg("kMDItemContentType")
+      .arg("-raw")
+      .arg(path),
+    MDFIND_TIMEOUT,
+  )?;
+
+  if !output.status.success() {
+    return None;
+  }
+  let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  if text.is_empty() || text == "(null)" {
+    None
+  } else {
+    Some(text)
+  }
+}
+
+fn reveal_in_finder_impl(path: String) -> Result<(), PlatformError> {
+  let target = expand_user_path(&path)?;
+  // 只把“不存在”视为无效选择，权限等其他错误原样返回
+  match fs::metadata(&target) {
+    Ok(_) => {}
+    Err(err) if err.kind() == ErrorKind::NotFound => {
+      // 应用已被卸载但 LSHandlers 中仍保留旧记录时会出现这种情况
+      return Err(PlatformError::InvalidSelection(format!(
+        "路径不存在，应用可能已被卸载或移动: {}",
+        target.display()
+      )));
+    }
+    Err(err) => return Err(err.into()),
+  }
+
+  let output = Command::new("open").arg("-R").arg(&target).output()?;
+  if !output.status.success() {
+    return Err(PlatformError::Command(format!(
+      "open -R 执行失败: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  Ok(())
+}
+
+fn resolve_existing_file(file_path: &str) -> Result<PathBuf, PlatformError> {
+  let file = expand_user_path(file_path)?;
+  if !file.exists() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "文件不存在: {}",
+      file.display()
+    )));
+  }
+  Ok(file)
+}
+
+fn open_file_in_app(file: &Path, app_path: &Path) -> Result<(), PlatformError> {
+  // 仅临时使用指定应用打开文件，不修改默认关联；路径作为独立参数传入，无需转义空格
+  let output = Command::new("open")
+    .arg("-a")
+    .arg(app_path)
+    .arg(file)
+    .output()?;
+
+  if !output.status.success() {
+    return Err(PlatformError::Command(format!(
+      "open 执行失败: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  Ok(())
+}
+
+// 比重启 cfprefsd 重得多：会重新扫描所有应用并重建 LaunchServices 数据库，耗时可能达数十秒，
+// 只应在用户主动点击“修复关联”时调用
+fn rebuild_launch_services_impl(
+  deep: bool,
+  mut on_progress: impl FnMut(&str),
+) -> Result<u64, PlatformError> {
+  let started = Instant::now();
+  let mut args = vec!["-kill", "-r", "-v", "-domain", "local", "-domain", "user"];
+  // 系统域包含所有系统自带应用，重建耗时明显更长，只在深度修复时处理
+  if deep {
+    args.extend(["-domain", "system"]);
+  }
+
+  let mut child = Command::new(LSREGISTER_PATH)
+    .args(&args)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()?;
+
+  let stderr_reader = child.stderr.take().map(|mut stderr| {
+    thread::spawn(move || {
+      let mut buffer = String::new();
+      let _ = stderr.read_to_string(&mut buffer);
+      buffer
+    })
+  });
+
+  if let Some(stdout) = child.stdout.take() {
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+      let line = line.trim();
+      if !line.is_empty() {
+        on_progress(line);
+      }
+    }
+  }
+
+  let status = child.wait()?;
+  let stderr = stderr_reader
+    .and_then(|reader| reader.join().ok())
+    .unwrap_or_default();
+  if !status.success() {
+    return Err(PlatformError::Command(format!(
+      "lsregister 执行失败: {}",
+      stderr.trim()
+    )));
   }
 
-  let path = launch_services_plist_path()?;
-  if let Some(parent) = path.parent() {
-    fs::create_dir_all(parent)?;
+  Ok(started.elapsed().as_millis() as u64)
+}
+
+fn restart_preferences_daemon() -> bool {
+  // 部分受管控的电脑禁止结束系统进程，可在 settings.json 中关闭这一步，修改会在重新登录后生效
+  if load_settings().skip_preferences_refresh {
+    logger::info("已按设置跳过 killall cfprefsd");
+    return false;
   }
-  plist::to_file_xml(path, &value)?;
 
   // 重启相关服务以使更改生效
-  let _ = Command::new("killall").arg("cfprefsd").status();
+  match Command::new("killall").arg("cfprefsd").output() {
+    Ok(output) if output.status.success() => true,
+    Ok(output) => {
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      logger::warn(format!("killall cfprefsd 失败: {}", stderr.trim()));
+      false
+    }
+    Err(err) => {
+      logger::warn(format!("无法执行 killall cfprefsd: {err}"));
+      false
+    }
+  }
+}
 
-  Ok(())
+fn load_settings() -> Settings {
+  let Ok(path) = settings_path() else {
+    return Settings::default();
+  };
+  let Ok(text) = fs::read_to_string(&path) else {
+    return Settings::default();
+  };
+  serde_json::from_str(&text).unwrap_or_else(|err| {
+    logger::warn(format!("settings.json 格式无效，使用默认设置: {err}"));
+    Settings::default()
+  })
 }
 
-fn resolve_app_bundle_path(raw_path: &str) -> Result<PathBuf, PlatformError> {
+fn expand_user_path(raw_path: &str) -> Result<PathBuf, PlatformError> {
   let trimmed = raw_path.trim();
-  let initial = if let Some(url_like) = trimmed.strip_prefix("file://") {
+  let expanded = if let Some(url_like) = trimmed.strip_prefix("file://") {
     if trimmed.starts_with("file:///") {
       Url::parse(trimmed)
         .map_err(|err| PlatformError::InvalidSelection(err.to_string()))?
@@ -683,7 +4637,13 @@ fn resolve_app_bundle_path(raw_path: &str) -> Result<PathBuf, PlatformError> {
     PathBuf::from(trimmed)
   };
 
-  let expanded = fs::canonicalize(&initial).unwrap_or(initial);
+  Ok(expanded)
+}
+
+fn resolve_app_bundle_path(raw_path: &str) -> Result<PathBuf, PlatformError> {
+  let trimmed = raw_path.trim();
+  let initial = expand_user_path(trimmed)?;
+  let expanded = resolve_symlinks(initial);
 
   if !expanded.exists() {
     return Err(PlatformError::InvalidSelection(format!(
@@ -710,13 +4670,42 @@ fn resolve_app_bundle_path(raw_path: &str) -> Result<PathBuf, PlatformError> {
     )));
   };
 
+  if !bundle_path.join("Contents").join("Info.plist").is_file() {
+    return Err(PlatformError::InvalidSelection(format!(
+      "{} 缺少 Contents/Info.plist，不是完整的应用包",
+      bundle_path.display()
+    )));
+  }
+
   Ok(bundle_path)
 }
 
+const MAX_SYMLINK_HOPS: usize = 16;
+
+// canonicalize 会解析路径中的所有符号链接；失败时（例如中间某一级不可访问）逐级读取链接目标
+fn resolve_symlinks(path: PathBuf) -> PathBuf {
+  if let Ok(canonical) = fs::canonicalize(&path) {
+    return canonical;
+  }
+
+  let mut current = path;
+  for _ in 0..MAX_SYMLINK_HOPS {
+    let Ok(target) = fs::read_link(&current) else {
+      break;
+    };
+    current = match current.parent() {
+      Some(parent) if target.is_relative() => parent.join(target),
+      _ => target,
+    };
+  }
+  current
+}
+
 fn upsert_extension_handler(
   handlers: &mut Vec<Value>,
   extension: &str,
   bundle_id: &str,
+  role: HandlerRole,
 ) {
   for handler in handlers.iter_mut() {
     if let Value::Dictionary(dict) = handler {
@@ -729,10 +4718,8 @@ fn upsert_extension_handler(
         .and_then(Value::as_string);
 
       if tag.as_deref() == Some(extension) && tag_class == Some("public.filename-extension") {
-        dict.insert(
-          "LSHandlerRoleAll".to_string(),
-          Value::String(bundle_id.to_string()),
-        );
+        // 只替换角色键，条目中的其他键（如 LSHandlerPreferredVersions）保持不变
+        set_role_handler(dict, bundle_id, role);
         return;
       }
     }
@@ -747,10 +4734,7 @@ fn upsert_extension_handler(
     "LSHandlerContentTagClass".to_string(),
     Value::String("public.filename-extension".into()),
   );
-  new_dict.insert(
-    "LSHandlerRoleAll".to_string(),
-    Value::String(bundle_id.to_string()),
-  );
+  set_role_handler(&mut new_dict, bundle_id, role);
   handlers.push(Value::Dictionary(new_dict));
 }
 
@@ -758,15 +4742,14 @@ fn upsert_content_type_handler(
   handlers: &mut Vec<Value>,
   content_type: &str,
   bundle_id: &str,
+  role: HandlerRole,
 ) {
   for handler in handlers.iter_mut() {
     if let Value::Dictionary(dict) = handler {
       let handler_content_type = dict.get("LSHandlerContentType").and_then(Value::as_string);
       if handler_content_type.as_deref() == Some(content_type) {
-        dict.insert(
-          "LSHandlerRoleAll".to_string(),
-          Value::String(bundle_id.to_string()),
-        );
+        // 只替换角色键，保留条目中的其他键
+        set_role_handler(dict, bundle_id, role);
         return;
       }
     }
@@ -777,18 +4760,86 @@ fn upsert_content_type_handler(
     "LSHandlerContentType".to_string(),
     Value::String(content_type.to_string()),
   );
-  new_dict.insert(
-    "LSHandlerRoleAll".to_string(),
-    Value::String(bundle_id.to_string()),
-  );
+  set_role_handler(&mut new_dict, bundle_id, role);
   handlers.push(Value::Dictionary(new_dict));
 }
 
-fn extension_to_content_type(ext: &str) -> Option<&'static str> {
+fn remove_extension_handler(handlers: &mut Vec<Value>, extension: &str) -> bool {
+  let before = handlers.len();
+  handlers.retain(|handler| {
+    let Some(dict) = handler.as_dictionary() else {
+      return true;
+    };
+    let tag = dict
+      .get("LSHandlerContentTag")
+      .and_then(Value::as_string)
+      .map(str::to_lowercase);
+    let tag_class = dict
+      .get("LSHandlerContentTagClass")
+      .and_then(Value::as_string);
+
+    !(tag.as_deref() == Some(extension) && tag_class == Some("public.filename-extension"))
+  });
+  handlers.len() != before
+}
+
+fn remove_content_type_handler(handlers: &mut Vec<Value>, content_type: &str) -> bool {
+  let before = handlers.len();
+  handlers.retain(|handler| {
+    handler
+      .as_dictionary()
+      .and_then(|dict| dict.get("LSHandlerContentType"))
+      .and_then(Value::as_string)
+      != Some(content_type)
+  });
+  handlers.len() != before
+}
+
+fn extension_to_content_type(ext: &str) -> Option<String> {
+  // 用户在 content_types.json 中的映射优先于内置表
+  let normalized = ensure_extension_normalized(ext);
+  if let Some(content_type) = user_content_types()
+    .ok()
+    .and_then(|mut overrides| overrides.remove(&normalized))
+  {
+    return Some(content_type);
+  }
+
   EXTENSION_TO_CONTENT_TYPE
     .iter()
     .find(|(key, _)| key.eq_ignore_ascii_case(ext))
-    .map(|(_, value)| *value)
+    .map(|(_, value)| value.to_string())
+}
+
+fn get_content_type_for_extension_impl(
+  extension: String,
+) -> Result<ContentTypeInfo, PlatformError> {
+  let normalized = validate_extension(&extension)?;
+  let content_type = extension_to_content_type(&normalized)
+    .or_else(|| preferred_content_type_for_extension(&normalized))
+    .ok_or_else(|| PlatformError::MissingInfo(format!("无法确定 .{normalized} 的内容类型")))?;
+
+  // 系统没有登记的扩展名会得到 dyn. 开头的动态类型，通常没有应用声明支持
+  Ok(ContentTypeInfo {
+    is_dynamic: content_type.starts_with("dyn."),
+    extension: normalized,
+    content_type,
+  })
+}
+
+fn user_content_types() -> Result<BTreeMap<String, String>, PlatformError> {
+  load_cached(&USER_CONTENT_TYPES, load_content_type_overrides)
+}
+
+fn load_content_type_overrides() -> Result<BTreeMap<String, String>, PlatformError> {
+  let stored: BTreeMap<String, String> = read_json_store(&content_types_config_path()?)?;
+  Ok(
+    stored
+      .into_iter()
+      .map(|(ext, uti)| (ensure_extension_normalized(&ext), uti.trim().to_string()))
+      .filter(|(ext, uti)| !ext.is_empty() && !uti.is_empty())
+      .collect(),
+  )
 }
 
 fn humanize_bundle_id(bundle_id: &str) -> String {
@@ -814,6 +4865,10 @@ fn humanize_bundle_id(bundle_id: &str) -> String {
 }
 
 fn copy_default_handler_for_content_type(content_type: &str) -> Option<String> {
+  copy_default_role_handler_for_content_type(content_type, LS_ROLES_ALL)
+}
+
+fn copy_default_role_handler_for_content_type(content_type: &str, roles: u32) -> Option<String> {
   let content_c = CString::new(content_type).ok()?;
   unsafe {
     let content_cf =
@@ -821,37 +4876,142 @@ fn copy_default_handler_for_content_type(content_type: &str) -> Option<String> {
     if content_cf.is_null() {
       return None;
     }
-    let handler_cf = LSCopyDefaultRoleHandlerForContentType(content_cf, LS_ROLES_ALL);
+    let handler_cf = LSCopyDefaultRoleHandlerForContentType(content_cf, roles);
     CFRelease(content_cf);
     if handler_cf.is_null() {
       return None;
     }
-    let mut buf = vec![0u8; 1024];
-    let ok = CFStringGetCString(
-      handler_cf,
-      buf.as_mut_ptr() as *mut c_char,
-      buf.len() as isize,
-      CFSTRING_ENCODING_UTF8,
-    );
+    let handler = cfstring_to_string(handler_cf);
     CFRelease(handler_cf);
-    if ok != 0 {
-      let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
-      String::from_utf8(buf[..len].to_vec()).ok()
-    } else {
-      None
+    handler
+  }
+}
+
+fn default_application_path_for_content_type(content_type: &str) -> Option<PathBuf> {
+  unsafe {
+    let content_cf = cfstring_from_str(content_type)?;
+    let url_cf =
+      LSCopyDefaultApplicationURLForContentType(content_cf, LS_ROLES_ALL, std::ptr::null_mut());
+    CFRelease(content_cf);
+    if url_cf.is_null() {
+      return None;
+    }
+
+    let path_cf = CFURLCopyFileSystemPath(url_cf, CF_URL_POSIX_PATH_STYLE);
+    CFRelease(url_cf);
+    let path = cfstring_to_string(path_cf);
+    if !path_cf.is_null() {
+      CFRelease(path_cf);
     }
+    path.map(PathBuf::from).filter(|path| path.exists())
   }
 }
 
-fn system_default_bundle_id_for_extension(ext: &str) -> Option<String> {
-  if let Some(content_type) = extension_to_content_type(ext) {
-    copy_default_handler_for_content_type(content_type)
+fn copy_all_handlers_for_content_type(content_type: &str) -> Vec<String> {
+  let Ok(content_c) = CString::new(content_type) else {
+    return Vec::new();
+  };
+  unsafe {
+    let content_cf =
+      CFStringCreateWithCString(kCFAllocatorDefault, content_c.as_ptr(), CFSTRING_ENCODING_UTF8);
+    if content_cf.is_null() {
+      return Vec::new();
+    }
+    let handlers_cf = LSCopyAllRoleHandlersForContentType(content_cf, LS_ROLES_ALL);
+    CFRelease(content_cf);
+    if handlers_cf.is_null() {
+      return Vec::new();
+    }
+    let count = CFArrayGetCount(handlers_cf);
+    let handlers = (0..count)
+      .filter_map(|idx| cfstring_to_string(CFArrayGetValueAtIndex(handlers_cf, idx)))
+      .collect();
+    CFRelease(handlers_cf);
+    handlers
+  }
+}
+
+fn application_paths_for_bundle_id(bundle_id: &str) -> Vec<PathBuf> {
+  unsafe {
+    let Some(bundle_cf) = cfstring_from_str(bundle_id) else {
+      return Vec::new();
+    };
+    let urls_cf = LSCopyApplicationURLsForBundleIdentifier(bundle_cf, std::ptr::null_mut());
+    CFRelease(bundle_cf);
+    if urls_cf.is_null() {
+      return Vec::new();
+    }
+
+    let count = CFArrayGetCount(urls_cf);
+    let paths = (0..count)
+      .filter_map(|idx| {
+        let path_cf =
+          CFURLCopyFileSystemPath(CFArrayGetValueAtIndex(urls_cf, idx), CF_URL_POSIX_PATH_STYLE);
+        let path = cfstring_to_string(path_cf);
+        if !path_cf.is_null() {
+          CFRelease(path_cf);
+        }
+        path.map(PathBuf::from)
+      })
+      .collect();
+    CFRelease(urls_cf);
+    paths
+  }
+}
+
+// 返回的 CFString 由调用方负责 CFRelease
+unsafe fn cfstring_from_str(value: &str) -> Option<CFStringRef> {
+  let value_c = CString::new(value).ok()?;
+  let value_cf =
+    CFStringCreateWithCString(kCFAllocatorDefault, value_c.as_ptr(), CFSTRING_ENCODING_UTF8);
+  if value_cf.is_null() {
+    None
+  } else {
+    Some(value_cf)
+  }
+}
+
+// 不会释放传入的 CFString，调用方负责其生命周期
+unsafe fn cfstring_to_string(string_cf: CFStringRef) -> Option<String> {
+  if string_cf.is_null() {
+    return None;
+  }
+  let mut buf = vec![0u8; 1024];
+  let ok = CFStringGetCString(
+    string_cf,
+    buf.as_mut_ptr() as *mut c_char,
+    buf.len() as isize,
+    CFSTRING_ENCODING_UTF8,
+  );
+  if ok != 0 {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec()).ok()
   } else {
-    let generic = format!("public.{}", ext);
-    copy_default_handler_for_content_type(&generic)
+    None
+  }
+}
+
+fn lookup_content_type(ext: &str) -> String {
+  // 查询时 dyn. 动态类型同样有效，只有在系统也无法给出类型时才退回猜测的 public.<扩展名>
+  match extension_to_content_type(ext).or_else(|| preferred_content_type_for_extension(ext)) {
+    Some(content_type) => content_type,
+    None => format!("public.{}", ext),
   }
 }
 
+// 内置表或用户映射优先，其余扩展名使用系统登记的类型；dyn. 动态类型没有应用声明支持，视为未知
+fn resolved_content_type(ext: &str) -> Option<String> {
+  extension_to_content_type(ext).or_else(|| {
+    preferred_content_type_for_extension(ext).filter(|uti| !uti.starts_with("dyn."))
+  })
+}
+
+fn system_default_bundle_id_for_extension(ext: &str) -> Option<String> {
+  copy_default_handler_for_content_type(&lookup_content_type(ext))
+}
+
+const LS_ROLES_VIEWER: u32 = 0x00000002;
+const LS_ROLES_EDITOR: u32 = 0x00000004;
 const LS_ROLES_ALL: u32 = 0xFFFFFFFF;
 
 #[link(name = "CoreServices", kind = "framework")]
@@ -865,9 +5025,37 @@ extern "C" {
     in_content_type: CFStringRef,
     in_role: u32,
   ) -> CFStringRef;
+  fn LSCopyDefaultApplicationURLForContentType(
+    in_content_type: CFStringRef,
+    in_role_mask: u32,
+    out_error: *mut CFTypeRef,
+  ) -> CFURLRef;
+  fn LSCopyAllRoleHandlersForContentType(
+    in_content_type: CFStringRef,
+    in_role: u32,
+  ) -> CFArrayRef;
+  fn LSCopyDefaultHandlerForURLScheme(in_url_scheme: CFStringRef) -> CFStringRef;
+  fn LSCopyApplicationURLsForBundleIdentifier(
+    in_bundle_identifier: CFStringRef,
+    out_error: *mut CFTypeRef,
+  ) -> CFArrayRef;
+  fn UTTypeConformsTo(in_uti: CFStringRef, in_conforms_to_uti: CFStringRef) -> u8;
+  fn UTTypeCreatePreferredIdentifierForTag(
+    in_tag_class: CFStringRef,
+    in_tag: CFStringRef,
+    in_conforming_to_uti: CFStringRef,
+  ) -> CFStringRef;
+  fn LSSetDefaultHandlerForURLScheme(
+    in_url_scheme: CFStringRef,
+    in_handler_bundle_id: CFStringRef,
+  ) -> i32;
 }
 
-fn set_launchservices_default(content_type: &str, bundle_id: &str) -> Result<(), PlatformError> {
+fn set_launchservices_default(
+  content_type: &str,
+  bundle_id: &str,
+  role: HandlerRole,
+) -> Result<(), PlatformError> {
   let content_c = CString::new(content_type)
     .map_err(|_| PlatformError::InvalidSelection(format!("非法的内容类型: {content_type}")))?;
   let bundle_c = CString::new(bundle_id)
@@ -892,7 +5080,7 @@ fn set_launchservices_default(content_type: &str, bundle_id: &str) -> Result<(),
     }
 
     let status =
-      LSSetDefaultRoleHandlerForContentType(content_cf, LS_ROLES_ALL, bundle_cf);
+      LSSetDefaultRoleHandlerForContentType(content_cf, role_mask(role), bundle_cf);
 
     CFRelease(content_cf);
     CFRelease(bundle_cf);
@@ -907,38 +5095,183 @@ fn set_launchservices_default(content_type: &str, bundle_id: &str) -> Result<(),
   }
 }
 
-fn set_extension_handler_by_tag(extension: &str, bundle_id: &str) -> Result<(), PlatformError> {
+fn preferred_content_type_for_extension(extension: &str) -> Option<String> {
+  let cache = PREFERRED_CONTENT_TYPES.get_or_init(|| Mutex::new(HashMap::new()));
+  let key = extension.to_ascii_lowercase();
+  if let Some(cached) = cache.lock().ok().and_then(|cache| cache.get(&key).cloned()) {
+    return cached;
+  }
+
+  let content_type = create_preferred_content_type(&key);
+  // dyn. 动态类型说明还没有应用声明该扩展名，新安装的应用声明后系统会给出正式的 UTI，不能缓存
+  let is_dynamic = content_type.as_deref().is_some_and(|uti| uti.starts_with("dyn."));
+  if !is_dynamic {
+    if let Ok(mut cache) = cache.lock() {
+      cache.insert(key, content_type.clone());
+    }
+  }
+  content_type
+}
+
+fn create_preferred_content_type(extension: &str) -> Option<String> {
+  unsafe {
+    let tag_class_cf = cfstring_from_str("public.filename-extension")?;
+    let Some(tag_cf) = cfstring_from_str(extension) else {
+      CFRelease(tag_class_cf);
+      return None;
+    };
+
+    let uti_cf = UTTypeCreatePreferredIdentifierForTag(tag_class_cf, tag_cf, std::ptr::null());
+    CFRelease(tag_class_cf);
+    CFRelease(tag_cf);
+    if uti_cf.is_null() {
+      return None;
+    }
+    let uti = cfstring_to_string(uti_cf);
+    CFRelease(uti_cf);
+    uti
+  }
+}
+
+fn content_type_conforms_to(content_type: &str, parent: &str) -> bool {
+  unsafe {
+    let Some(content_type_cf) = cfstring_from_str(content_type) else {
+      return false;
+    };
+    let Some(parent_cf) = cfstring_from_str(parent) else {
+      CFRelease(content_type_cf);
+      return false;
+    };
+    let conforms = UTTypeConformsTo(content_type_cf, parent_cf) != 0;
+    CFRelease(content_type_cf);
+    CFRelease(parent_cf);
+    conforms
+  }
+}
+
+fn copy_default_handler_for_url_scheme(scheme: &str) -> Option<String> {
+  unsafe {
+    let scheme_cf = cfstring_from_str(scheme)?;
+    let handler_cf = LSCopyDefaultHandlerForURLScheme(scheme_cf);
+    CFRelease(scheme_cf);
+    if handler_cf.is_null() {
+      return None;
+    }
+    let handler = cfstring_to_string(handler_cf);
+    CFRelease(handler_cf);
+    handler
+  }
+}
+
+fn set_url_scheme_default(scheme: &str, bundle_id: &str) -> Result<(), PlatformError> {
+  unsafe {
+    let Some(scheme_cf) = cfstring_from_str(scheme) else {
+      return Err(PlatformError::InvalidSelection(format!("非法的 URL 协议: {scheme}")));
+    };
+    let Some(bundle_cf) = cfstring_from_str(bundle_id) else {
+      CFRelease(scheme_cf);
+      return Err(PlatformError::InvalidSelection(format!("非法的应用 ID: {bundle_id}")));
+    };
+
+    let status = LSSetDefaultHandlerForURLScheme(scheme_cf, bundle_cf);
+
+    CFRelease(scheme_cf);
+    CFRelease(bundle_cf);
+
+    if status == 0 {
+      Ok(())
+    } else {
+      Err(PlatformError::Command(format!(
+        "LSSetDefaultHandlerForURLScheme 失败: {status}"
+      )))
+    }
+  }
+}
+
+fn find_duti_path() -> Option<PathBuf> {
+  let path_dirs = env::var_os("PATH")
+    .map(|paths| env::split_paths(&paths).collect::<Vec<_>>())
+    .unwrap_or_default();
+  path_dirs
+    .into_iter()
+    .chain(DUTI_SEARCH_DIRS.iter().map(PathBuf::from))
+    .map(|dir| dir.join("duti"))
+    .find(|candidate| candidate.is_file())
+}
+
+fn check_duti_impl() -> DutiStatus {
+  let Some(path) = find_duti_path() else {
+    return DutiStatus::default();
+  };
+
+  let version = Command::new(&path)
+    .arg("-V")
+    .output()
+    .ok()
+    .filter(|output| output.status.success())
+    .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    .filter(|version| !version.is_empty());
+
+  DutiStatus {
+    installed: true,
+    path: Some(path.to_string_lossy().into_owned()),
+    version,
+  }
+}
+
+fn set_extension_handler_by_tag(
+  extension: &str,
+  bundle_id: &str,
+  role: HandlerRole,
+) -> Result<(), PlatformError> {
+  let Some(duti_path) = find_duti_path() else {
+    logger::warn("未找到 duti 命令，尝试备用方法");
+    return set_extension_directly(extension, bundle_id, role);
+  };
+
   // 尝试使用duti命令设置，这是macOS推荐的命令行工具
-  let output = Command::new("duti")
+  let output = Command::new(duti_path)
     .arg("-s")
     .arg(bundle_id)
     .arg(extension)
-    .arg("all")
+    .arg(duti_role(role))
     .output();
 
   match output {
     Ok(result) => {
       if result.status.success() {
-        eprintln!("使用 duti 成功设置 .{} 的默认应用为 {}", extension, bundle_id);
+        logger::info(format!("使用 duti 成功设置 .{} 的默认应用为 {}", extension, bundle_id));
         Ok(())
       } else {
         let stderr = String::from_utf8_lossy(&result.stderr);
-        eprintln!("duti 命令失败: {}, 尝试备用方法", stderr);
+        logger::warn(format!("duti 命令失败: {}, 尝试备用方法", stderr));
         // 如果duti失败，尝试直接使用LS API
-        set_extension_directly(extension, bundle_id)
+        set_extension_directly(extension, bundle_id, role)
       }
     }
     Err(err) => {
-      eprintln!("无法执行 duti 命令: {}, 尝试备用方法", err);
+      logger::warn(format!("无法执行 duti 命令: {}, 尝试备用方法", err));
       // 如果duti不可用，尝试直接使用LS API
-      set_extension_directly(extension, bundle_id)
+      set_extension_directly(extension, bundle_id, role)
     }
   }
 }
 
-fn set_extension_directly(extension: &str, bundle_id: &str) -> Result<(), PlatformError> {
-  // 尝试创建一个动态的内容类型
-  let content_type = format!("public.{}", extension);
+fn duti_role(role: HandlerRole) -> &'static str {
+  match role {
+    HandlerRole::All => "all",
+    HandlerRole::Viewer => "viewer",
+    HandlerRole::Editor => "editor",
+  }
+}
+
+fn set_extension_directly(
+  extension: &str,
+  bundle_id: &str,
+  role: HandlerRole,
+) -> Result<(), PlatformError> {
+  // 未声明的扩展名由系统分配 dyn. 动态类型，凭空拼出的 public.<扩展名> 并不存在
+  let content_type = lookup_content_type(extension);
 
   let content_c = CString::new(content_type.as_str())
     .map_err(|_| PlatformError::InvalidSelection(format!("非法的内容类型: {content_type}")))?;
@@ -964,18 +5297,328 @@ fn set_extension_directly(extension: &str, bundle_id: &str) -> Result<(), Platfo
     }
 
     let status =
-      LSSetDefaultRoleHandlerForContentType(content_cf, LS_ROLES_ALL, bundle_cf);
+      LSSetDefaultRoleHandlerForContentType(content_cf, role_mask(role), bundle_cf);
 
     CFRelease(content_cf);
     CFRelease(bundle_cf);
 
     if status == 0 {
-      eprintln!("使用 LS API 成功设置 .{} 的默认应用为 {}", extension, bundle_id);
+      logger::info(format!("使用 LS API 成功设置 .{} 的默认应用为 {}", extension, bundle_id));
       Ok(())
     } else {
-      eprintln!("LS API 设置失败: {}, 将仅依赖 plist 配置", status);
+      logger::error(format!("LS API 设置失败: {}, 将仅依赖 plist 配置", status));
       // 即使LS API失败，我们已经设置了plist配置，所以返回Ok
       Ok(())
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 每个测试使用独立的临时目录，避免并行运行时互相干扰
+  fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("default-application-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn fabricate_app(dir: &Path, name: &str, bundle_id: Option<&str>) -> PathBuf {
+    let app = dir.join(format!("{name}.app"));
+    let contents = app.join("Contents");
+    fs::create_dir_all(&contents).unwrap();
+    let mut info = Dictionary::new();
+    info.insert("CFBundleName".into(), Value::String(name.into()));
+    if let Some(bundle_id) = bundle_id {
+      info.insert("CFBundleIdentifier".into(), Value::String(bundle_id.into()));
+    }
+    Value::Dictionary(info)
+      .to_file_xml(contents.join("Info.plist"))
+      .unwrap();
+    app
+  }
+
+  #[test]
+  fn compound_extensions_normalize_to_last_component() {
+    assert_eq!(ensure_extension_normalized("tar.gz"), "gz");
+    assert_eq!(ensure_extension_normalized(".TAR.GZ"), "gz");
+    assert_eq!(ensure_extension_normalized("archive.tar.gz"), "gz");
+    assert_eq!(ensure_extension_normalized("d.ts"), "ts");
+    assert_eq!(ensure_extension_normalized(".d.ts"), "ts");
+  }
+
+  #[test]
+  fn plain_extensions_are_only_lowercased() {
+    assert_eq!(ensure_extension_normalized("gz"), "gz");
+    assert_eq!(ensure_extension_normalized(".GZ"), "gz");
+  }
+
+  const PLIST_WITH_EXTRA_KEYS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+  <key>LSHandlers</key>
+  <array>
+    <dict>
+      <key>LSHandlerContentTag</key>
+      <string>md</string>
+      <key>LSHandlerContentTagClass</key>
+      <string>public.filename-extension</string>
+      <key>LSHandlerPreferredVersions</key>
+      <dict>
+        <key>LSHandlerRoleAll</key>
+        <string>-</string>
+      </dict>
+      <key>LSHandlerRoleAll</key>
+      <string>com.apple.textedit</string>
+    </dict>
+    <dict>
+      <key>LSHandlerContentType</key>
+      <string>net.daringfireball.markdown</string>
+      <key>LSHandlerModificationDate</key>
+      <real>700000000</real>
+      <key>LSHandlerRoleAll</key>
+      <string>com.apple.textedit</string>
+    </dict>
+    <string>unexpected</string>
+  </array>
+  <key>LSUnknownTopLevelKey</key>
+  <true/>
+</dict>
+</plist>"#;
+
+  fn round_trip(value: &Value) -> Value {
+    let mut buffer = Vec::new();
+    value.to_writer_xml(&mut buffer).unwrap();
+    Value::from_reader_xml(buffer.as_slice()).unwrap()
+  }
+
+  #[test]
+  fn upsert_preserves_unknown_handler_keys_across_round_trip() {
+    let mut value = Value::from_reader_xml(PLIST_WITH_EXTRA_KEYS.as_bytes()).unwrap();
+    let handlers = handlers_from_value_mut(&mut value).unwrap();
+    upsert_extension_handler(handlers, "md", "com.example.editor", HandlerRole::All);
+    upsert_content_type_handler(
+      handlers,
+      "net.daringfireball.markdown",
+      "com.example.editor",
+      HandlerRole::All,
+    );
+
+    let value = round_trip(&value);
+    let root = value.as_dictionary().unwrap();
+    assert!(root.contains_key("LSUnknownTopLevelKey"));
+
+    let handlers = handlers_from_value(&value).unwrap();
+    assert_eq!(handlers.len(), 3);
+    assert_eq!(handlers[2].as_string(), Some("unexpected"));
+
+    let tag_entry = handlers[0].as_dictionary().unwrap();
+    assert!(tag_entry.contains_key("LSHandlerPreferredVersions"));
+    assert_eq!(
+      tag_entry.get("LSHandlerRoleAll").and_then(Value::as_string),
+      Some("com.example.editor")
+    );
+
+    let content_type_entry = handlers[1].as_dictionary().unwrap();
+    assert!(content_type_entry.contains_key("LSHandlerModificationDate"));
+    assert_eq!(
+      content_type_entry.get("LSHandlerRoleAll").and_then(Value::as_string),
+      Some("com.example.editor")
+    );
+  }
+
+  #[test]
+  fn upsert_appends_entry_for_new_extension() {
+    let mut handlers = vec![Value::String("unexpected".into())];
+    upsert_extension_handler(&mut handlers, "rs", "com.example.editor", HandlerRole::All);
+
+    assert_eq!(handlers.len(), 2);
+    let entry = handlers[1].as_dictionary().unwrap();
+    assert_eq!(entry.get("LSHandlerContentTag").and_then(Value::as_string), Some("rs"));
+    assert_eq!(
+      entry.get("LSHandlerContentTagClass").and_then(Value::as_string),
+      Some("public.filename-extension")
+    );
+  }
+
+  #[test]
+  fn bundle_id_is_read_from_fabricated_app() {
+    let dir = scratch_dir("bundle-id");
+    let app = fabricate_app(&dir, "Editor", Some("com.example.editor"));
+    let unnamed = fabricate_app(&dir, "Unnamed", None);
+
+    assert_eq!(bundle_id_from_path(&app).unwrap(), "com.example.editor");
+    assert!(matches!(bundle_id_from_path(&unnamed), Err(PlatformError::MissingInfo(_))));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn only_the_registered_copy_of_a_bundle_counts() {
+    let dir = scratch_dir("registered");
+    let installed = fabricate_app(&dir.join("Applications"), "Editor", Some("com.example.editor"));
+    let mounted = fabricate_app(&dir.join("Volumes"), "Editor", Some("com.example.editor"));
+    let registered = vec![installed.clone()];
+
+    assert!(path_is_registered(&installed, &registered));
+    assert!(!path_is_registered(&mounted, &registered));
+    assert!(!path_is_registered(&installed, &[]));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn no_change_requires_plist_and_launch_services_to_agree() {
+    let target = "com.example.editor";
+    assert!(is_already_default(Some(target), Some("COM.EXAMPLE.EDITOR"), target));
+    // plist 已指向目标应用但 LaunchServices 仍在使用旧应用，需要重新设置
+    assert!(!is_already_default(Some(target), Some("com.apple.textedit"), target));
+    assert!(!is_already_default(Some(target), None, target));
+    assert!(!is_already_default(None, Some(target), target));
+  }
+
+  #[test]
+  fn symlink_into_bundle_resolves_to_enclosing_app() {
+    let dir = scratch_dir("symlink");
+    let app = fabricate_app(&dir, "Editor", Some("com.example.editor"));
+    let binary = app.join("Contents").join("MacOS").join("Editor");
+    fs::create_dir_all(binary.parent().unwrap()).unwrap();
+    fs::write(&binary, b"").unwrap();
+
+    let binary_link = dir.join("editor");
+    let bundle_link = dir.join("Shortcut.app");
+    std::os::unix::fs::symlink(&binary, &binary_link).unwrap();
+    std::os::unix::fs::symlink(&app, &bundle_link).unwrap();
+
+    let expected = fs::canonicalize(&app).unwrap();
+    for link in [&binary_link, &bundle_link] {
+      let resolved = resolve_app_bundle_path(link.to_str().unwrap()).unwrap();
+      assert_eq!(resolved, expected);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn bundle_without_info_plist_is_rejected() {
+    let dir = scratch_dir("no-info");
+    let app = dir.join("Broken.app");
+    fs::create_dir_all(app.join("Contents").join("MacOS")).unwrap();
+
+    let result = resolve_app_bundle_path(app.to_str().unwrap());
+    assert!(matches!(result, Err(PlatformError::InvalidSelection(_))));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn backups_in_the_same_millisecond_sort_by_sequence() {
+    let first = PathBuf::from(backup_file_name(1_700_000_000_123, 0));
+    let second = PathBuf::from(backup_file_name(1_700_000_000_123, 1));
+    assert_eq!(backup_order(&first), Some((1_700_000_000_123, 0)));
+    assert_eq!(backup_order(&second), Some((1_700_000_000_123, 1)));
+    assert!(backup_order(&second) > backup_order(&first));
+
+    // 早期以秒命名的备份仍能识别，并排在之后的毫秒备份之前
+    let legacy = PathBuf::from(backup_file_name(1_600_000_000, 0));
+    assert_eq!(backup_order(&legacy), Some((1_600_000_000_000, 0)));
+    assert_eq!(backup_order(Path::new("notes.json")), None);
+  }
+
+  #[test]
+  fn duti_lines_map_roles_and_ignore_trailing_comments() {
+    let parsed = |line| parse_duti_line(line).map(|result| result.map_err(|entry| entry.reason));
+
+    assert_eq!(
+      parsed("com.apple.TextEdit\tpublic.plain-text\tall # 纯文本"),
+      Some(Ok(("com.apple.TextEdit", "public.plain-text", HandlerRole::All)))
+    );
+    assert_eq!(
+      parsed("com.apple.Preview .pdf VIEWER"),
+      Some(Ok(("com.apple.Preview", ".pdf", HandlerRole::Viewer)))
+    );
+    assert_eq!(
+      parsed("com.example.Editor md editor"),
+      Some(Ok(("com.example.Editor", "md", HandlerRole::Editor)))
+    );
+    assert_eq!(parsed("   # 整行注释"), None);
+    assert_eq!(parsed(""), None);
+    assert!(matches!(parsed("com.apple.Terminal public.unix-executable shell"), Some(Err(_))));
+    assert!(matches!(parsed("com.example.App .txt sometimes"), Some(Err(_))));
+    assert!(matches!(parsed("com.example.App too many fields here"), Some(Err(_))));
+  }
+
+  #[test]
+  fn duti_url_scheme_lines_are_skipped_by_scheme() {
+    let Some(Err(entry)) = parse_duti_line("com.apple.Safari\thttp # 浏览器") else {
+      panic!("两列的 URL scheme 行应当被跳过");
+    };
+    assert_eq!(entry.extension, "http");
+  }
+
+  #[test]
+  fn duti_targets_distinguish_dotted_extensions_from_utis() {
+    assert_eq!(duti_target_extensions(".MD"), vec!["md".to_string()]);
+    assert_eq!(duti_target_extensions("md"), vec!["md".to_string()]);
+    let markdown = duti_target_extensions("net.daringfireball.markdown");
+    assert!(markdown.contains(&"md".to_string()));
+    assert!(markdown.contains(&"markdown".to_string()));
+    assert!(duti_target_extensions("com.example.unknown-type").is_empty());
+  }
+
+  fn change_record(timestamp: u64) -> ChangeRecord {
+    ChangeRecord {
+      timestamp,
+      extension: "md".into(),
+      previous_bundle_id: None,
+      previous_live_bundle_id: None,
+      bundle_id: format!("com.example.app{timestamp}"),
+      application_path: String::new(),
+      role: HandlerRole::All,
+    }
+  }
+
+  #[test]
+  fn corrupt_history_file_starts_over_instead_of_failing() {
+    let dir = scratch_dir("history-corrupt");
+    let path = dir.join(HISTORY_FILE_NAME);
+    fs::write(&path, "{ not json").unwrap();
+    assert!(load_change_history_from(&path).unwrap().is_empty());
+
+    // 损坏的文件会在下一次写入时被替换
+    append_change_record_to(&path, change_record(1)).unwrap();
+    let records = load_change_history_from(&path).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].timestamp, 1);
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn history_keeps_only_the_newest_entries() {
+    let dir = scratch_dir("history-cap");
+    let path = dir.join(HISTORY_FILE_NAME);
+    let existing: Vec<ChangeRecord> = (0..MAX_HISTORY_ENTRIES as u64).map(change_record).collect();
+    write_json_store(&path, &existing).unwrap();
+
+    append_change_record_to(&path, change_record(MAX_HISTORY_ENTRIES as u64)).unwrap();
+    let records = load_change_history_from(&path).unwrap();
+    assert_eq!(records.len(), MAX_HISTORY_ENTRIES);
+    assert_eq!(records.first().map(|record| record.timestamp), Some(1));
+    assert_eq!(
+      records.last().map(|record| record.timestamp),
+      Some(MAX_HISTORY_ENTRIES as u64)
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn change_history_is_returned_newest_first_up_to_the_limit() {
+    let records: Vec<ChangeRecord> = (0..5).map(change_record).collect();
+    let newest: Vec<u64> = newest_changes(records.clone(), 3)
+      .iter()
+      .map(|record| record.timestamp)
+      .collect();
+    assert_eq!(newest, vec![4, 3, 2]);
+    assert_eq!(newest_changes(records, 10).len(), 5);
+  }
+}
@@ -0,0 +1,360 @@
+// Linux 通过 xdg-mime 读取和修改默认应用；其余命令沿用 platform_unsupported 中的实现
+pub use crate::platform_unsupported::*;
+
+use crate::{
+  category_for_extension, default_extensions, ApplyStatus, AssociationSource, AssociationStatus,
+  FileAssociation, HandlerRole, SetDefaultResult,
+};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const EXTENSION_TO_MIME_TYPE: &[(&str, &str)] = &[
+  // Office
+  ("doc", "application/msword"),
+  ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+  ("xls", "application/vnd.ms-excel"),
+  ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+  ("ppt", "application/vnd.ms-powerpoint"),
+  ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+  ("txt", "text/plain"),
+  ("pdf", "application/pdf"),
+  ("png", "image/png"),
+  ("jpg", "image/jpeg"),
+  ("jpeg", "image/jpeg"),
+  ("gif", "image/gif"),
+  ("csv", "text/csv"),
+  ("mp3", "audio/mpeg"),
+  ("mp4", "video/mp4"),
+  ("mov", "video/quicktime"),
+  ("avi", "video/x-msvideo"),
+  ("zip", "application/zip"),
+  ("rar", "application/vnd.rar"),
+  ("7z", "application/x-7z-compressed"),
+  ("tar", "application/x-tar"),
+  ("gz", "application/gzip"),
+  ("json", "application/json"),
+  ("xml", "application/xml"),
+  ("html", "text/html"),
+  ("htm", "text/html"),
+  ("css", "text/css"),
+  ("js", "application/javascript"),
+  ("ts", "text/x-typescript"),
+  ("jsx", "text/jsx"),
+  ("tsx", "text/tsx"),
+  ("md", "text/markdown"),
+  ("markdown", "text/markdown"),
+  ("py", "text/x-python"),
+  ("java", "text/x-java"),
+  ("cpp", "text/x-c++src"),
+  ("c", "text/x-csrc"),
+  ("h", "text/x-chdr"),
+  ("hpp", "text/x-c++hdr"),
+  ("sh", "application/x-shellscript"),
+  ("bash", "application/x-shellscript"),
+  ("zsh", "application/x-shellscript"),
+  ("fish", "application/x-shellscript"),
+  ("sql", "application/sql"),
+  ("db", "application/vnd.sqlite3"),
+  ("sqlite", "application/vnd.sqlite3"),
+  ("log", "text/x-log"),
+  ("ini", "text/x-ini"),
+  ("cfg", "text/plain"),
+  ("conf", "text/plain"),
+  ("yaml", "application/yaml"),
+  ("yml", "application/yaml"),
+  ("toml", "application/toml"),
+  ("env", "text/plain"),
+  ("key", "application/pgp-keys"),
+  ("pem", "application/x-pem-file"),
+  ("crt", "application/x-x509-ca-cert"),
+];
+
+pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
+  Ok(default_extensions().map(resolve_association).collect())
+}
+
+pub fn list_file_associations_with_icons_inner() -> Result<Vec<FileAssociation>, String> {
+  list_file_associations_inner()
+}
+
+pub fn get_association_for_extension_inner(extension: String) -> Result<FileAssociation, String> {
+  let normalized = normalize_extension(&extension)?;
+  Ok(resolve_association(&normalized))
+}
+
+pub fn set_default_application_for_extension_inner(
+  extension: String,
+  application_path: String,
+  _force: bool,
+  _role: HandlerRole,
+) -> Result<SetDefaultResult, String> {
+  let normalized = normalize_extension(&extension)?;
+  let desktop_id = desktop_id_from_path(&application_path)?;
+  let mime_type = lookup_mime_type(&normalized);
+
+  let output = Command::new("xdg-mime")
+    .args(["default", &desktop_id, &mime_type])
+    .output()
+    .map_err(|err| format!("执行 xdg-mime 失败: {err}"))?;
+
+  if !output.status.success() {
+    return Err(format!(
+      "xdg-mime 设置默认应用失败: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+
+  // 部分桌面环境会缓存 mimeapps.list，需要重新启动文件管理器后才会生效
+  match query_default_desktop_id(&mime_type) {
+    Some(current) if current == desktop_id => Ok(SetDefaultResult {
+      status: ApplyStatus::Applied,
+      detail: None,
+      refreshed: true,
+    }),
+    current => Ok(SetDefaultResult {
+      status: ApplyStatus::PendingRelaunch,
+      detail: Some(format!(
+        "{mime_type} 当前的默认应用为 {}，预期为 {desktop_id}",
+        current.as_deref().unwrap_or("(无)")
+      )),
+      refreshed: true,
+    }),
+  }
+}
+
+fn normalize_extension(extension: &str) -> Result<String, String> {
+  let normalized = extension.trim().trim_start_matches('.').to_lowercase();
+  if normalized.is_empty() {
+    return Err("扩展名不能为空".into());
+  }
+  Ok(normalized)
+}
+
+fn lookup_mime_type(ext: &str) -> String {
+  match EXTENSION_TO_MIME_TYPE
+    .iter()
+    .find(|(key, _)| key.eq_ignore_ascii_case(ext))
+  {
+    Some((_, mime_type)) => mime_type.to_string(),
+    None => format!("application/x-{ext}"),
+  }
+}
+
+fn resolve_association(ext: &str) -> FileAssociation {
+  let mime_type = lookup_mime_type(ext);
+  let Some(desktop_id) = query_default_desktop_id(&mime_type) else {
+    return FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: "未设置默认应用".into(),
+      application_path: String::new(),
+      ..Default::default()
+    };
+  };
+
+  let source = if user_overrides_mime_type(&mime_type) {
+    AssociationSource::UserOverride
+  } else {
+    AssociationSource::SystemDefault
+  };
+
+  match find_desktop_file(&desktop_id) {
+    Some(path) => FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: application_name_from_path(&path)
+        .unwrap_or_else(|| desktop_id.trim_end_matches(".desktop").to_string()),
+      application_path: path.display().to_string(),
+      status: AssociationStatus::Resolved,
+      source,
+      is_user_override: source == AssociationSource::UserOverride,
+      ..Default::default()
+    },
+    None => FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: desktop_id.trim_end_matches(".desktop").to_string(),
+      application_path: String::new(),
+      status: AssociationStatus::AppNotFound,
+      source,
+      is_user_override: source == AssociationSource::UserOverride,
+      ..Default::default()
+    },
+  }
+}
+
+fn query_default_desktop_id(mime_type: &str) -> Option<String> {
+  let output = Command::new("xdg-mime")
+    .args(["query", "default", mime_type])
+    .output()
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  let desktop_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  if desktop_id.is_empty() {
+    None
+  } else {
+    Some(desktop_id)
+  }
+}
+
+fn user_overrides_mime_type(mime_type: &str) -> bool {
+  // 用户通过“打开方式”修改的默认应用写在 $XDG_CONFIG_HOME/mimeapps.list 的 [Default Applications] 中
+  let config_home = env::var_os("XDG_CONFIG_HOME")
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+  config_home
+    .and_then(|dir| fs::read_to_string(dir.join("mimeapps.list")).ok())
+    .is_some_and(|contents| mimeapps_sets_default(&contents, mime_type))
+}
+
+fn mimeapps_sets_default(contents: &str, mime_type: &str) -> bool {
+  let mut in_defaults = false;
+  for line in contents.lines() {
+    let line = line.trim();
+    if line.starts_with('[') {
+      in_defaults = line == "[Default Applications]";
+      continue;
+    }
+    if in_defaults {
+      if let Some((key, value)) = line.split_once('=') {
+        if key.trim() == mime_type && !value.trim().is_empty() {
+          return true;
+        }
+      }
+    }
+  }
+  false
+}
+
+fn desktop_id_from_path(application_path: &str) -> Result<String, String> {
+  let trimmed = application_path.trim();
+  let desktop_id = Path::new(trimmed)
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or(trimmed);
+
+  if !desktop_id.ends_with(".desktop") {
+    return Err(format!("请选择 .desktop 文件: {trimmed}"));
+  }
+  Ok(desktop_id.to_string())
+}
+
+fn application_dirs() -> Vec<PathBuf> {
+  // 按 XDG Base Directory 规范的优先级查找 applications 目录
+  let data_home = env::var_os("XDG_DATA_HOME")
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+  let data_dirs = env::var("XDG_DATA_DIRS")
+    .ok()
+    .filter(|dirs| !dirs.is_empty())
+    .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+  data_home
+    .into_iter()
+    .chain(data_dirs.split(':').map(PathBuf::from))
+    .map(|dir| dir.join("applications"))
+    .collect()
+}
+
+fn find_desktop_file(desktop_id: &str) -> Option<PathBuf> {
+  application_dirs()
+    .into_iter()
+    .find_map(|dir| find_desktop_file_in(&dir, desktop_id))
+}
+
+// desktop id 中的任意 '-' 都可能对应子目录，例如 org-kde-kate.desktop -> org/kde/kate.desktop；
+// 只进入实际存在的子目录，不需要枚举所有组合
+fn find_desktop_file_in(dir: &Path, desktop_id: &str) -> Option<PathBuf> {
+  let direct = dir.join(desktop_id);
+  if direct.is_file() {
+    return Some(direct);
+  }
+  desktop_id.match_indices('-').find_map(|(index, _)| {
+    let subdir = dir.join(&desktop_id[..index]);
+    if subdir.is_dir() {
+      find_desktop_file_in(&subdir, &desktop_id[index + 1..])
+    } else {
+      None
+    }
+  })
+}
+
+fn application_name_from_path(desktop_path: &Path) -> Option<String> {
+  let contents = fs::read_to_string(desktop_path).ok()?;
+  let mut in_desktop_entry = false;
+  for line in contents.lines() {
+    let line = line.trim();
+    if line.starts_with('[') {
+      in_desktop_entry = line == "[Desktop Entry]";
+      continue;
+    }
+    if !in_desktop_entry {
+      continue;
+    }
+    if let Some(name) = line.strip_prefix("Name=") {
+      let name = name.trim();
+      if !name.is_empty() {
+        return Some(name.to_string());
+      }
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 每个测试使用独立的临时目录，避免并行运行时互相干扰
+  fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("default-application-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn desktop_ids_resolve_through_every_dash_level() {
+    let dir = scratch_dir("desktop-ids");
+    fs::create_dir_all(dir.join("org/kde")).unwrap();
+    fs::write(dir.join("org/kde/kate.desktop"), "[Desktop Entry]\nName=Kate\n").unwrap();
+    fs::create_dir_all(dir.join("kde4")).unwrap();
+    fs::write(dir.join("kde4/okular-app.desktop"), "").unwrap();
+
+    assert_eq!(
+      find_desktop_file_in(&dir, "org-kde-kate.desktop"),
+      Some(dir.join("org/kde/kate.desktop"))
+    );
+    // 只有第一个 '-' 对应子目录，其余的保留在文件名中
+    assert_eq!(
+      find_desktop_file_in(&dir, "kde4-okular-app.desktop"),
+      Some(dir.join("kde4/okular-app.desktop"))
+    );
+    assert_eq!(find_desktop_file_in(&dir, "org-kde-missing.desktop"), None);
+
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn mimeapps_default_applications_count_as_user_overrides() {
+    let contents = "\
+[Added Associations]
+text/plain=gedit.desktop;
+
+[Default Applications]
+text/markdown = org.gnome.TextEditor.desktop
+image/png=
+";
+    assert!(mimeapps_sets_default(contents, "text/markdown"));
+    // 只在 [Added Associations] 中出现的类型不是默认应用
+    assert!(!mimeapps_sets_default(contents, "text/plain"));
+    // 值为空的条目不算用户设置
+    assert!(!mimeapps_sets_default(contents, "image/png"));
+    assert!(!mimeapps_sets_default(contents, "application/pdf"));
+  }
+}
@@ -0,0 +1,421 @@
+use crate::{
+  default_extensions, AddExtensionsResult, ApplicationVerdict, AssociationProgress,
+  AssociationStatus, AssociationVerification, BatchSetResult, BundleInfo, CandidateApplication,
+  ChangeRecord, Conflict, ContentTypeInfo, DefaultHandlerRequest, DutiStatus, ExtensionDiagnosis,
+  FileAssociation, FileInspection, FullDiskAccessReport, FullDiskAccessStatus, HandlerRole,
+  HandlerSelector, ImportReport, InstalledApplication, LaunchServicesBackup, RawHandlerEntry,
+  RemoveExtensionResult, SavedProfile, UrlSchemeAssociation, DEFAULT_URL_SCHEMES,
+};
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+use crate::{category_for_extension, SetDefaultResult};
+use std::collections::HashMap;
+
+pub fn check_full_disk_access_inner() -> Result<FullDiskAccessStatus, String> {
+  Ok(FullDiskAccessStatus::Granted)
+}
+
+pub fn open_full_disk_access_settings_inner() -> Result<(), String> {
+  Err("仅支持在 macOS 上打开系统设置".into())
+}
+
+// Windows 和 Linux 模块有自己的实现，这里只在其他平台上编译，避免出现未使用的函数
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
+  Ok(
+    default_extensions()
+      .map(|ext| FileAssociation {
+        extension: ext.to_string(),
+        category: category_for_extension(ext),
+        application_name: "Unsupported platform".into(),
+        application_path: String::new(),
+        ..Default::default()
+      })
+      .collect(),
+  )
+}
+
+pub fn list_file_associations_streaming_inner(
+  mut on_resolved: impl FnMut(AssociationProgress),
+) -> Result<usize, String> {
+  // 其他平台解析很快，一次性取得后逐条发送即可
+  let associations = crate::platform::list_file_associations_inner()?;
+  let total = associations.len();
+  for (index, association) in associations.into_iter().enumerate() {
+    on_resolved(AssociationProgress {
+      index,
+      total,
+      association,
+    });
+  }
+  Ok(total)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn get_association_for_extension_inner(extension: String) -> Result<FileAssociation, String> {
+  Ok(FileAssociation {
+    category: category_for_extension(&extension),
+    extension,
+    application_name: "Unsupported platform".into(),
+    application_path: String::new(),
+    ..Default::default()
+  })
+}
+
+pub fn list_handlers_for_extension_inner(
+  _extension: String,
+) -> Result<Vec<FileAssociation>, String> {
+  Ok(Vec::new())
+}
+
+pub fn list_installed_applications_inner() -> Result<Vec<InstalledApplication>, String> {
+  Ok(Vec::new())
+}
+
+pub fn add_extension_inner(_extension: String) -> Result<Vec<FileAssociation>, String> {
+  Err("仅支持在 macOS 上自定义扩展名列表".into())
+}
+
+pub fn remove_extension_inner(_extension: String) -> Result<Vec<FileAssociation>, String> {
+  Err("仅支持在 macOS 上自定义扩展名列表".into())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn set_default_application_for_extension_inner(
+  _extension: String,
+  _application_path: String,
+  _force: bool,
+  _role: HandlerRole,
+) -> Result<SetDefaultResult, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn reset_extension_to_system_default_inner(
+  _extension: String,
+) -> Result<FileAssociation, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn list_url_scheme_handlers_inner() -> Result<Vec<UrlSchemeAssociation>, String> {
+  Ok(
+    DEFAULT_URL_SCHEMES
+      .iter()
+      .map(|scheme| UrlSchemeAssociation {
+        scheme: scheme.to_string(),
+        application_name: "Unsupported platform".into(),
+        application_path: String::new(),
+        status: AssociationStatus::Unset,
+      })
+      .collect(),
+  )
+}
+
+pub fn set_default_application_for_url_scheme_inner(
+  _scheme: String,
+  _application_path: String,
+) -> Result<(), String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn list_candidate_apps_for_extension_inner(
+  _extension: String,
+) -> Result<Vec<CandidateApplication>, String> {
+  Ok(Vec::new())
+}
+
+pub fn set_default_application_for_extensions_inner(
+  _extensions: Vec<String>,
+  _application_path: String,
+) -> Result<BatchSetResult, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn export_associations_inner() -> Result<String, String> {
+  Err("仅支持在 macOS 上导出默认应用配置".into())
+}
+
+pub fn import_associations_inner(_json: String, _dry_run: bool) -> Result<ImportReport, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn list_launch_services_backups_inner() -> Result<Vec<LaunchServicesBackup>, String> {
+  Ok(Vec::new())
+}
+
+pub fn restore_launch_services_backup_inner(
+  _backup_name: String,
+) -> Result<Vec<FileAssociation>, String> {
+  Err("仅支持在 macOS 上恢复 LaunchServices 备份".into())
+}
+
+pub fn export_associations_to_file_inner(_path: String) -> Result<usize, String> {
+  Err("仅支持在 macOS 上导出默认应用配置".into())
+}
+
+pub fn import_associations_from_file_inner(
+  _path: String,
+  _dry_run: bool,
+) -> Result<ImportReport, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn list_file_associations_with_icons_inner() -> Result<Vec<FileAssociation>, String> {
+  list_file_associations_inner()
+}
+
+pub fn backup_launch_services_inner() -> Result<String, String> {
+  Err("仅支持在 macOS 上备份 LaunchServices 配置".into())
+}
+
+pub fn clear_bundle_cache_inner() -> Result<(), String> {
+  Ok(())
+}
+
+pub fn get_bundle_info_inner(_application_path: String) -> Result<BundleInfo, String> {
+  Err("仅支持在 macOS 上读取应用信息".into())
+}
+
+pub fn get_default_browser_inner() -> Result<UrlSchemeAssociation, String> {
+  Err("仅支持在 macOS 上读取默认浏览器".into())
+}
+
+pub fn set_default_browser_inner(
+  _application_path: String,
+) -> Result<DefaultHandlerRequest, String> {
+  Err("仅支持在 macOS 上修改默认浏览器".into())
+}
+
+pub fn get_default_mail_client_inner() -> Result<UrlSchemeAssociation, String> {
+  Err("仅支持在 macOS 上读取默认邮件应用".into())
+}
+
+pub fn set_default_mail_client_inner(
+  _application_path: String,
+) -> Result<DefaultHandlerRequest, String> {
+  Err("仅支持在 macOS 上修改默认邮件应用".into())
+}
+
+pub fn open_with_inner(_file_path: String, _application_path: String) -> Result<(), String> {
+  Err("仅支持在 macOS 上使用指定应用打开文件".into())
+}
+
+pub fn rebuild_launch_services_inner(
+  _deep: bool,
+  _on_progress: impl FnMut(&str),
+) -> Result<u64, String> {
+  Err("仅支持在 macOS 上重建 LaunchServices 数据库".into())
+}
+
+pub fn open_file_with_application_inner(
+  _file_path: String,
+  _application_path: String,
+) -> Result<(), String> {
+  Err("仅支持在 macOS 上使用指定应用打开文件".into())
+}
+
+pub fn content_type_for_path_inner(_file_path: String) -> Result<String, String> {
+  Err("仅支持在 macOS 上查询文件类型".into())
+}
+
+pub fn set_content_type_mapping_inner(_extension: String, _uti: String) -> Result<(), String> {
+  Err("仅支持在 macOS 上修改内容类型映射".into())
+}
+
+pub fn reveal_in_finder_inner(_path: String) -> Result<(), String> {
+  Err("仅支持在 macOS 上在访达中显示".into())
+}
+
+pub fn get_app_icon_inner(_application_path: String, _size: u32) -> Result<String, String> {
+  Err("仅支持在 macOS 上读取应用图标".into())
+}
+
+pub fn search_applications_inner(
+  _query: String,
+  _limit: usize,
+) -> Result<Vec<InstalledApplication>, String> {
+  Ok(Vec::new())
+}
+
+pub fn add_extensions_inner(_extensions: Vec<String>) -> Result<AddExtensionsResult, String> {
+  Err("仅支持在 macOS 上添加文件类型".into())
+}
+
+pub fn remove_extension_and_handler_inner(
+  _extension: String,
+  _clear_handler: bool,
+) -> Result<RemoveExtensionResult, String> {
+  Err("仅支持在 macOS 上移除文件类型".into())
+}
+
+pub fn inspect_file_inner(_file_path: String) -> Result<FileInspection, String> {
+  Err("仅支持在 macOS 上识别文件类型".into())
+}
+
+pub fn get_change_history_inner(_limit: usize) -> Result<Vec<ChangeRecord>, String> {
+  Ok(Vec::new())
+}
+
+pub fn preview_set_default_application_inner(
+  _extension: String,
+  _application_path: String,
+  _force: bool,
+  _role: HandlerRole,
+) -> Result<String, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn undo_last_change_inner() -> Result<FileAssociation, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn check_duti_inner() -> Result<DutiStatus, String> {
+  Ok(DutiStatus::default())
+}
+
+pub fn get_content_type_for_extension_inner(_extension: String) -> Result<ContentTypeInfo, String> {
+  Err("仅支持在 macOS 上查询内容类型".into())
+}
+
+pub fn list_tracked_extensions_inner() -> Result<Vec<String>, String> {
+  // 与 macOS 保持一致，按字母顺序返回
+  let mut extensions: Vec<String> = default_extensions().map(|ext| ext.to_string()).collect();
+  extensions.sort();
+  Ok(extensions)
+}
+
+pub fn set_default_application_for_content_type_inner(
+  _content_type: String,
+  _application_path: String,
+) -> Result<Vec<String>, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn list_raw_handlers_inner() -> Result<Vec<RawHandlerEntry>, String> {
+  Ok(Vec::new())
+}
+
+pub fn delete_handler_entry_inner(
+  _selector: HandlerSelector,
+  _force: bool,
+) -> Result<Vec<RawHandlerEntry>, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn detect_conflicts_inner() -> Result<Vec<Conflict>, String> {
+  Ok(Vec::new())
+}
+
+pub fn validate_application_inner(
+  _application_path: String,
+  _extension: String,
+) -> Result<ApplicationVerdict, String> {
+  Err("仅支持在 macOS 上检查应用".into())
+}
+
+pub fn check_app_supports_extension_inner(
+  _application_path: String,
+  _extension: String,
+) -> Result<bool, String> {
+  Err("仅支持在 macOS 上检查应用".into())
+}
+
+pub fn set_default_application_for_category_inner(
+  _category: String,
+  _application_path: String,
+) -> Result<BatchSetResult, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn pin_extension_inner(_extension: String) -> Result<Vec<String>, String> {
+  Err("仅支持在 macOS 上固定扩展名".into())
+}
+
+pub fn unpin_extension_inner(_extension: String) -> Result<Vec<String>, String> {
+  Err("仅支持在 macOS 上固定扩展名".into())
+}
+
+pub fn list_pinned_extensions_inner() -> Result<Vec<String>, String> {
+  Ok(Vec::new())
+}
+
+pub fn list_all_handlers_for_extension_inner(
+  _extension: String,
+) -> Result<Vec<CandidateApplication>, String> {
+  Ok(Vec::new())
+}
+
+pub fn set_extension_note_inner(_extension: String, _note: String) -> Result<(), String> {
+  Err("仅支持在 macOS 上保存备注".into())
+}
+
+pub fn set_pinned_extensions_inner(_order: Vec<String>) -> Result<Vec<String>, String> {
+  Err("仅支持在 macOS 上固定扩展名".into())
+}
+
+pub fn verify_association_inner(_extension: String) -> Result<AssociationVerification, String> {
+  Err("仅支持在 macOS 上验证默认应用".into())
+}
+
+pub fn export_duti_config_inner(_path: String) -> Result<usize, String> {
+  Err("仅支持在 macOS 上导出 duti 配置".into())
+}
+
+pub fn import_duti_config_inner(_path: String, _dry_run: bool) -> Result<ImportReport, String> {
+  Err("仅支持在 macOS 上导入 duti 配置".into())
+}
+
+pub fn set_default_application_by_bundle_id_inner(
+  _extension: String,
+  _bundle_id: String,
+  _allow_missing: bool,
+) -> Result<FileAssociation, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn apply_profile_inner(
+  _profile_name: String,
+  _app_overrides: HashMap<String, String>,
+) -> Result<BatchSetResult, String> {
+  Err("仅支持在 macOS 上修改默认应用".into())
+}
+
+pub fn save_profile_inner(_name: String) -> Result<SavedProfile, String> {
+  Err("仅支持在 macOS 上保存预设".into())
+}
+
+pub fn list_profiles_inner() -> Result<Vec<SavedProfile>, String> {
+  Ok(Vec::new())
+}
+
+pub fn apply_saved_profile_inner(_name: String) -> Result<ImportReport, String> {
+  Err("仅支持在 macOS 上应用预设".into())
+}
+
+pub fn delete_profile_inner(_name: String) -> Result<Vec<SavedProfile>, String> {
+  Err("仅支持在 macOS 上删除预设".into())
+}
+
+pub fn reset_all_to_system_defaults_inner(
+  _clear_extensions: bool,
+) -> Result<Vec<FileAssociation>, String> {
+  Err("仅支持在 macOS 上恢复默认应用".into())
+}
+
+pub fn set_search_roots_inner(_paths: Vec<String>) -> Result<Vec<String>, String> {
+  Err("仅支持在 macOS 上设置应用搜索目录".into())
+}
+
+pub fn list_search_roots_inner() -> Result<Vec<String>, String> {
+  Ok(Vec::new())
+}
+
+pub fn check_full_disk_access_detailed_inner() -> Result<FullDiskAccessReport, String> {
+  Ok(FullDiskAccessReport {
+    status: FullDiskAccessStatus::Granted,
+    probes: Vec::new(),
+  })
+}
+
+pub fn diagnose_extension_inner(_extension: String) -> Result<ExtensionDiagnosis, String> {
+  Err("仅支持在 macOS 上诊断扩展名".into())
+}
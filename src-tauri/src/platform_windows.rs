@@ -0,0 +1,195 @@
+// Windows 仅支持读取默认应用；其余命令沿用 platform_unsupported 中的实现
+pub use crate::platform_unsupported::*;
+
+use crate::{
+  category_for_extension, default_extensions, ApplyStatus, AssociationSource, AssociationStatus,
+  FileAssociation, HandlerRole, SetDefaultResult,
+};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+const FILE_EXTS_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts";
+const DEFAULT_APPS_SETTINGS_URI: &str = "ms-settings:defaultapps";
+
+pub fn list_file_associations_inner() -> Result<Vec<FileAssociation>, String> {
+  Ok(default_extensions().map(resolve_association).collect())
+}
+
+pub fn list_file_associations_with_icons_inner() -> Result<Vec<FileAssociation>, String> {
+  list_file_associations_inner()
+}
+
+pub fn get_association_for_extension_inner(extension: String) -> Result<FileAssociation, String> {
+  let normalized = extension.trim().trim_start_matches('.').to_lowercase();
+  if normalized.is_empty() {
+    return Err("扩展名不能为空".into());
+  }
+  Ok(resolve_association(&normalized))
+}
+
+pub fn set_default_application_for_extension_inner(
+  extension: String,
+  _application_path: String,
+  _force: bool,
+  _role: HandlerRole,
+) -> Result<SetDefaultResult, String> {
+  // UserChoice 带有系统校验的 Hash，第三方程序写入会被 Windows 重置，只能引导用户在设置中确认
+  let status = Command::new("cmd")
+    .args(["/C", "start", "", DEFAULT_APPS_SETTINGS_URI])
+    .status()
+    .map_err(|err| format!("打开默认应用设置失败: {err}"))?;
+
+  if !status.success() {
+    return Err(format!("打开默认应用设置失败，退出状态: {status}"));
+  }
+
+  // 打开设置页面是预期流程，不是失败
+  Ok(SetDefaultResult {
+    status: ApplyStatus::ManualConfirmation,
+    detail: Some(format!(
+      "Windows 不允许直接修改 .{extension} 的默认应用，已打开“默认应用”设置，请在其中完成选择"
+    )),
+    refreshed: false,
+  })
+}
+
+fn resolve_association(ext: &str) -> FileAssociation {
+  // 用户在“打开方式”中的选择优先，其次是 HKEY_CLASSES_ROOT 中的全局关联
+  let user_choice =
+    reg_query_value(&format!(r"{FILE_EXTS_KEY}\.{ext}\UserChoice"), Some("ProgId"));
+  let (prog_id, source) = match user_choice {
+    Some(prog_id) => (Some(prog_id), AssociationSource::UserOverride),
+    None => (
+      reg_query_value(&format!(r"HKCR\.{ext}"), None),
+      AssociationSource::SystemDefault,
+    ),
+  };
+
+  let Some(prog_id) = prog_id else {
+    return FileAssociation {
+      extension: ext.to_string(),
+      category: category_for_extension(ext),
+      application_name: "未设置默认应用".into(),
+      application_path: String::new(),
+      ..Default::default()
+    };
+  };
+
+  // REG_EXPAND_SZ 中常见 %SystemRoot% 之类的变量，展开后才能检查文件是否存在
+  let application_path = reg_query_value(&format!(r"HKCR\{prog_id}\shell\open\command"), None)
+    .map(|command| expand_environment_strings(&command))
+    .and_then(|command| executable_from_command(&command))
+    .unwrap_or_default();
+
+  let application_name = Path::new(&application_path)
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .map(str::to_string)
+    .unwrap_or_else(|| prog_id.clone());
+
+  // ProgId 存在但找不到可执行文件，说明关联的应用已被卸载
+  let status = if Path::new(&application_path).is_file() {
+    AssociationStatus::Resolved
+  } else {
+    AssociationStatus::AppNotFound
+  };
+
+  FileAssociation {
+    extension: ext.to_string(),
+    category: category_for_extension(ext),
+    application_name,
+    application_path,
+    status,
+    source,
+    is_user_override: source == AssociationSource::UserOverride,
+    ..Default::default()
+  }
+}
+
+fn reg_query_value(key: &str, value_name: Option<&str>) -> Option<String> {
+  let mut command = Command::new("reg");
+  command.arg("query").arg(key);
+  match value_name {
+    Some(name) => command.arg("/v").arg(name),
+    None => command.arg("/ve"),
+  };
+
+  let output = command.output().ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  // 输出形如 "    ProgId    REG_SZ    VSCode.txt"
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  stdout.lines().find_map(|line| {
+    let (_, rest) = line.trim().split_once("REG_")?;
+    let (_, data) = rest.split_once(char::is_whitespace)?;
+    let data = data.trim();
+    if data.is_empty() || data == "(value not set)" {
+      None
+    } else {
+      Some(data.to_string())
+    }
+  })
+}
+
+// 与 ExpandEnvironmentStrings 一致：未定义的变量原样保留
+fn expand_environment_strings(value: &str) -> String {
+  let mut result = String::with_capacity(value.len());
+  let mut rest = value;
+  while let Some(start) = rest.find('%') {
+    result.push_str(&rest[..start]);
+    let after = &rest[start + 1..];
+    let Some(end) = after.find('%') else {
+      rest = &rest[start..];
+      break;
+    };
+    let name = &after[..end];
+    match env::var(name) {
+      Ok(expanded) if !name.is_empty() => result.push_str(&expanded),
+      _ => {
+        result.push('%');
+        result.push_str(name);
+        result.push('%');
+      }
+    }
+    rest = &after[end + 1..];
+  }
+  result.push_str(rest);
+  result
+}
+
+fn executable_from_command(command: &str) -> Option<String> {
+  let command = command.trim();
+  let executable = if let Some(quoted) = command.strip_prefix('"') {
+    quoted.split('"').next()?
+  } else {
+    command.split_whitespace().next()?
+  };
+
+  if executable.is_empty() {
+    None
+  } else {
+    Some(executable.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn expands_defined_variables_and_keeps_unknown_ones() {
+    env::set_var("DEFAULT_APPLICATION_TEST_ROOT", r"C:\Windows");
+    assert_eq!(
+      expand_environment_strings(r"%DEFAULT_APPLICATION_TEST_ROOT%\System32\notepad.exe %1"),
+      r"C:\Windows\System32\notepad.exe %1"
+    );
+    assert_eq!(
+      expand_environment_strings(r"%DEFAULT_APPLICATION_UNDEFINED%\app.exe"),
+      r"%DEFAULT_APPLICATION_UNDEFINED%\app.exe"
+    );
+    assert_eq!(expand_environment_strings("100%"), "100%");
+  }
+}
@@ -1,13 +1,130 @@
 import { useCallback, useEffect, useState, type KeyboardEvent } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
 import { open } from '@tauri-apps/plugin-dialog';
 
-type PermissionState = 'checking' | 'granted' | 'denied';
+type PermissionState = 'checking' | 'granted' | 'denied' | 'indeterminate';
+type FullDiskAccessStatus = 'granted' | 'denied' | 'indeterminate';
+
+type AssociationStatus = 'resolved' | 'handlerMissing' | 'appNotFound' | 'unset';
 
 type FileAssociation = {
   extension: string;
+  bundleIdentifier: string;
+  applicationName: string;
+  applicationPath: string;
+  iconBase64: string | null;
+  status: AssociationStatus;
+  category: string;
+  source: 'userOverride' | 'systemDefault' | 'none';
+  isUserOverride: boolean;
+  systemDefaultName: string | null;
+  isPinned: boolean;
+  note: string | null;
+  viewerBundleId: string | null;
+  editorBundleId: string | null;
+  contentType: string | null;
+};
+
+type AssociationProgress = {
+  index: number;
+  total: number;
+  association: FileAssociation;
+};
+
+type AssociationStreamComplete = {
+  total: number;
+  error: string | null;
+};
+
+type AddExtensionOutcome = {
+  input: string;
+  extension: string;
+  status: 'added' | 'alreadyAdded' | 'rejected';
+  reason: string | null;
+};
+
+type AddExtensionsResult = {
+  associations: FileAssociation[];
+  results: AddExtensionOutcome[];
+};
+
+type RemoveExtensionResult = {
+  associations: FileAssociation[];
+  handlerRemoved: boolean;
+};
+
+const statusHints: Partial<Record<AssociationStatus, string>> = {
+  handlerMissing: '关联记录不完整，当前使用系统默认应用',
+  appNotFound: '关联的应用已被删除或移动',
+};
+
+type FileInspection = {
+  extension: string | null;
+  contentType: string;
   applicationName: string;
   applicationPath: string;
+  isManaged: boolean;
+};
+
+type AssociationVerification = {
+  extension: string;
+  contentType: string;
+  expectedBundleId: string | null;
+  actualBundleId: string | null;
+  effective: boolean;
+};
+
+type DutiStatus = {
+  installed: boolean;
+  path: string | null;
+  version: string | null;
+};
+
+type SetDefaultResult = {
+  status:
+    | 'applied'
+    | 'pendingRelaunch'
+    | 'noChange'
+    | 'needsConfirmation'
+    | 'manualConfirmation';
+  detail: string | null;
+  refreshed: boolean;
+};
+
+// 应用没有声明支持该文件类型时，由用户确认是否仍要强制设置；用户取消时返回 null
+async function setDefaultApplication(extension: string, applicationPath: string) {
+  const result = await invoke<SetDefaultResult>('set_default_application_for_extension', {
+    extension,
+    applicationPath,
+  });
+  if (result.status !== 'needsConfirmation') {
+    return result;
+  }
+  const reason = result.detail ?? '该应用没有声明支持此文件类型';
+  if (!window.confirm(`${reason}，仍要将其设为默认应用吗？`)) {
+    return null;
+  }
+  return await invoke<SetDefaultResult>('set_default_application_for_extension', {
+    extension,
+    applicationPath,
+    force: true,
+  });
+}
+
+const describeSetResult = (extension: string, result: SetDefaultResult) => {
+  if (result.status === 'noChange') {
+    return `.${extension} 已经使用该应用打开，无需修改。`;
+  }
+  if (result.status === 'manualConfirmation') {
+    return result.detail ?? `请在系统设置中完成 .${extension} 默认应用的选择。`;
+  }
+  const message =
+    result.status === 'applied'
+      ? `已更新 .${extension} 的默认打开方式。`
+      : `已保存 .${extension} 的默认打开方式，需要重新启动访达或重新登录后生效。`;
+  return result.refreshed ? message : `${message}（未能刷新系统偏好设置缓存）`;
 };
 
 export default function App() {
@@ -19,6 +136,7 @@ export default function App() {
   const [newExtension, setNewExtension] = useState('');
   const [query, setQuery] = useState('');
   const [showTop, setShowTop] = useState(false);
+  const [dutiMissing, setDutiMissing] = useState(false);
 
   // Popular formats order for sorting (lower rank appears first)
   const popularOrder = [
@@ -38,15 +156,27 @@ export default function App() {
   ];
   const rank = new Map(popularOrder.map((ext, i) => [ext, i]));
 
+  // sort: pinned first (keeping the saved pin order from the backend), then by popularity rank,
+  // then alphabetically
+  const sortAssociations = (data: FileAssociation[]) =>
+    [...data].sort((a, b) => {
+      if (a.isPinned !== b.isPinned) return a.isPinned ? -1 : 1;
+      if (a.isPinned) return 0;
+      const ra = rank.get(a.extension.toLowerCase()) ?? Number.MAX_SAFE_INTEGER;
+      const rb = rank.get(b.extension.toLowerCase()) ?? Number.MAX_SAFE_INTEGER;
+      if (ra !== rb) return ra - rb;
+      return a.extension.localeCompare(b.extension);
+    });
+
   const checkPermission = useCallback(async () => {
     setPermission('checking');
     try {
-      const granted = await invoke<boolean>('check_full_disk_access');
-      setPermission(granted ? 'granted' : 'denied');
-      if (!granted) {
+      const status = await invoke<FullDiskAccessStatus>('check_full_disk_access');
+      setPermission(status);
+      if (status !== 'granted') {
         setFeedback(null);
       }
-      return granted;
+      return status === 'granted';
     } catch (err) {
       console.error(err);
       setError('无法检测磁盘访问权限，请稍后再试。');
@@ -55,23 +185,35 @@ export default function App() {
     }
   }, []);
 
+  // 后端逐条解析并通过事件发送结果，解析到一条就显示一条
   const fetchAssociations = useCallback(async () => {
     setLoading(true);
     setError(null);
+    const rows: FileAssociation[] = [];
+    let resolveComplete: (result: AssociationStreamComplete) => void = () => {};
+    const completed = new Promise<AssociationStreamComplete>((resolve) => {
+      resolveComplete = resolve;
+    });
+    const unlistenResolved = await listen<AssociationProgress>('association-resolved', (event) => {
+      rows[event.payload.index] = event.payload.association;
+      setAssociations(sortAssociations(rows.filter(Boolean)));
+    });
+    const unlistenComplete = await listen<AssociationStreamComplete>('association-complete', (event) =>
+      resolveComplete(event.payload),
+    );
     try {
-      const data = await invoke<FileAssociation[]>('list_file_associations');
-      // sort: by popularity rank first, then alphabetically
-      const sorted = [...data].sort((a, b) => {
-        const ra = rank.get(a.extension.toLowerCase()) ?? Number.MAX_SAFE_INTEGER;
-        const rb = rank.get(b.extension.toLowerCase()) ?? Number.MAX_SAFE_INTEGER;
-        if (ra !== rb) return ra - rb;
-        return a.extension.localeCompare(b.extension);
-      });
-      setAssociations(sorted);
+      await invoke('list_file_associations_streaming');
+      const result = await completed;
+      if (result.error) {
+        throw result.error;
+      }
+      setAssociations(sortAssociations(rows.filter(Boolean)));
     } catch (err) {
       console.error(err);
       setError('读取默认应用列表失败，请刷新或稍后再试。');
     } finally {
+      unlistenResolved();
+      unlistenComplete();
       setLoading(false);
     }
   }, []);
@@ -85,6 +227,83 @@ export default function App() {
     })();
   }, [checkPermission, fetchAssociations]);
 
+  // 没有预定义内容类型的扩展名优先通过 duti 设置，未安装时提示用户安装（仅 macOS）
+  useEffect(() => {
+    if (permission !== 'granted' || !navigator.userAgent.includes('Mac')) {
+      return;
+    }
+    invoke<DutiStatus>('check_duti')
+      .then((status) => setDutiMissing(!status.installed))
+      .catch((err) => console.error(err));
+  }, [permission]);
+
+  // 后台会定期检测权限，用户在系统设置中授权后立即刷新界面
+  useEffect(() => {
+    const unlisten = listen<FullDiskAccessStatus>('full-disk-access-changed', (event) => {
+      setPermission(event.payload);
+      if (event.payload === 'granted') {
+        setFeedback(null);
+        fetchAssociations();
+      }
+    });
+    return () => {
+      unlisten.then((dispose) => dispose());
+    };
+  }, [fetchAssociations]);
+
+  // 在访达“显示简介”等其他地方修改默认应用后，后台会通知界面重新读取
+  useEffect(() => {
+    if (permission !== 'granted') {
+      return;
+    }
+    const unlisten = listen('associations-changed', () => {
+      fetchAssociations();
+    });
+    return () => {
+      unlisten.then((dispose) => dispose());
+    };
+  }, [permission, fetchAssociations]);
+
+  // 把文件拖进窗口即可识别它的扩展名和当前默认应用
+  useEffect(() => {
+    if (permission !== 'granted') {
+      return;
+    }
+    const unlisten = getCurrentWebview().onDragDropEvent(async (event) => {
+      if (event.payload.type !== 'drop' || !event.payload.paths.length) {
+        return;
+      }
+      setError(null);
+      try {
+        const result = await invoke<FileInspection>('inspect_file', {
+          filePath: event.payload.paths[0],
+        });
+        const label = result.extension ? `.${result.extension}` : result.contentType;
+        setFeedback(`该文件的类型为 ${label}，默认应用为 ${result.applicationName}。`);
+        if (!result.extension) {
+          return;
+        }
+        // 尚未管理的扩展名由用户确认后再加入列表
+        if (!result.isManaged) {
+          if (!window.confirm(`.${result.extension} 还不在列表中，要加入列表吗？`)) {
+            return;
+          }
+          await invoke('add_extension', { extension: result.extension });
+        }
+        setQuery(result.extension);
+        await fetchAssociations();
+      } catch (err) {
+        console.error(err);
+        const message =
+          typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+        setError(message || '无法识别拖入的文件。');
+      }
+    });
+    return () => {
+      unlisten.then((dispose) => dispose());
+    };
+  }, [permission, fetchAssociations]);
+
   const handleOpenSettings = useCallback(async () => {
     setError(null);
     setFeedback(null);
@@ -93,7 +312,11 @@ export default function App() {
       setFeedback('已打开系统偏好设置，请在“完全磁盘访问”中开启权限。');
     } catch (err) {
       console.error(err);
-      setError('无法打开系统偏好设置，请手动前往"系统设置 > 隐私与安全 > 完全磁盘访问"。');
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(
+        `${message ? `${message}。` : ''}请手动前往"系统设置 > 隐私与安全 > 完全磁盘访问"。`,
+      );
     }
   }, []);
 
@@ -105,6 +328,37 @@ export default function App() {
     }
   }, [checkPermission, fetchAssociations]);
 
+  // 探测文件都不存在时无法判断权限，允许用户跳过检测直接读取
+  const handleContinueAnyway = useCallback(() => {
+    setError(null);
+    setFeedback(null);
+    setPermission('granted');
+    fetchAssociations();
+  }, [fetchAssociations]);
+
+  // 手动刷新时清空应用路径缓存，以便识别刚安装或移动过的应用
+  const handleRefreshList = useCallback(async () => {
+    try {
+      await invoke('clear_bundle_cache');
+    } catch (err) {
+      console.error(err);
+    }
+    fetchAssociations();
+  }, [fetchAssociations]);
+
+  // 只重新解析单个扩展名，避免修改一行就重新扫描整个列表
+  const refreshAssociation = useCallback(async (extension: string) => {
+    try {
+      const updated = await invoke<FileAssociation>('get_association_for_extension', { extension });
+      setAssociations((prev) =>
+        prev.map((item) => (item.extension === updated.extension ? updated : item)),
+      );
+    } catch (err) {
+      console.error(err);
+      fetchAssociations();
+    }
+  }, [fetchAssociations]);
+
   const handleModify = useCallback(
     async (extension: string) => {
       setError(null);
@@ -126,12 +380,12 @@ export default function App() {
           return;
         }
 
-        await invoke('set_default_application_for_extension', {
-          extension,
-          applicationPath: selection,
-        });
-        setFeedback(`已更新 .${extension} 的默认打开方式。`);
-        fetchAssociations();
+        const result = await setDefaultApplication(extension, selection);
+        if (!result) {
+          return;
+        }
+        setFeedback(describeSetResult(extension, result));
+        await refreshAssociation(extension);
       } catch (err) {
         console.error(err);
         setFeedback(null);
@@ -148,12 +402,47 @@ export default function App() {
         );
       }
     },
-    [fetchAssociations],
+    [refreshAssociation],
   );
 
+  // 一次粘贴多个扩展名时批量添加，不再逐个弹出应用选择对话框
+  const handleAddExtensions = useCallback(async (extensions: string[]) => {
+    setLoading(true);
+    try {
+      const result = await invoke<AddExtensionsResult>('add_extensions', { extensions });
+      setAssociations(sortAssociations(result.associations));
+      setNewExtension('');
+      const added = result.results.filter((item) => item.status === 'added');
+      const existing = result.results.filter((item) => item.status === 'alreadyAdded');
+      const rejected = result.results.filter((item) => item.status === 'rejected');
+      const parts = [`已添加 ${added.length} 个文件类型`];
+      if (existing.length) {
+        parts.push(`${existing.length} 个已在列表中`);
+      }
+      setFeedback(`${parts.join('，')}。`);
+      if (rejected.length) {
+        setError(
+          `以下扩展名无效：${rejected.map((item) => `${item.input}（${item.reason}）`).join('、')}`,
+        );
+      }
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '添加文件类型失败，请稍后再试。');
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
   const handleAddExtension = useCallback(async () => {
     setFeedback(null);
     setError(null);
+    const tokens = newExtension.split(/[\s,，]+/).filter(Boolean);
+    if (tokens.length > 1) {
+      await handleAddExtensions(tokens);
+      return;
+    }
     const normalized = newExtension.trim().replace(/^\.+/, '').toLowerCase();
     if (!normalized) {
       setError('请输入有效的扩展名。');
@@ -162,7 +451,7 @@ export default function App() {
     setLoading(true);
     try {
       const data = await invoke<FileAssociation[]>('add_extension', { extension: normalized });
-      setAssociations(data);
+      setAssociations(sortAssociations(data));
       setFeedback(`已添加 .${normalized} 文件类型。`);
       setNewExtension('');
 
@@ -182,12 +471,11 @@ export default function App() {
         });
 
         if (selection && !Array.isArray(selection)) {
-          await invoke('set_default_application_for_extension', {
-            extension: normalized,
-            applicationPath: selection,
-          });
-          setFeedback(`已为 .${normalized} 设置默认打开方式。`);
-          await fetchAssociations();
+          const result = await setDefaultApplication(normalized, selection);
+          if (result) {
+            setFeedback(describeSetResult(normalized, result));
+            await fetchAssociations();
+          }
         }
       } catch (err) {
         console.error(err);
@@ -209,7 +497,193 @@ export default function App() {
     } finally {
       setLoading(false);
     }
-  }, [newExtension, fetchAssociations]);
+  }, [newExtension, fetchAssociations, handleAddExtensions]);
+
+  const handleReset = useCallback(async (extension: string) => {
+    setFeedback(null);
+    setError(null);
+    try {
+      const updated = await invoke<FileAssociation>('reset_extension_to_system_default', {
+        extension,
+      });
+      setAssociations((prev) =>
+        prev.map((item) => (item.extension === updated.extension ? updated : item)),
+      );
+      setFeedback(`已将 .${extension} 恢复为系统默认打开方式。`);
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '恢复系统默认失败，请稍后再试。');
+    }
+  }, []);
+
+  const handleTogglePin = useCallback(async (item: FileAssociation) => {
+    setFeedback(null);
+    setError(null);
+    try {
+      await invoke<string[]>(item.isPinned ? 'unpin_extension' : 'pin_extension', {
+        extension: item.extension,
+      });
+      setAssociations((prev) =>
+        sortAssociations(
+          prev.map((row) =>
+            row.extension === item.extension ? { ...row, isPinned: !item.isPinned } : row,
+          ),
+        ),
+      );
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '更新固定状态失败，请稍后再试。');
+    }
+  }, []);
+
+  const handleEditNote = useCallback(async (item: FileAssociation) => {
+    const note = window.prompt(`为 .${item.extension} 添加备注（留空则删除）`, item.note ?? '');
+    if (note === null) {
+      return;
+    }
+    setFeedback(null);
+    setError(null);
+    try {
+      await invoke('set_extension_note', { extension: item.extension, note });
+      const trimmed = note.trim();
+      setAssociations((prev) =>
+        prev.map((row) =>
+          row.extension === item.extension ? { ...row, note: trimmed || null } : row,
+        ),
+      );
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '保存备注失败，请稍后再试。');
+    }
+  }, []);
+
+  const handleReveal = useCallback(async (item: FileAssociation) => {
+    setError(null);
+    try {
+      await invoke('reveal_in_finder', { path: item.applicationPath });
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '无法在访达中显示该应用，请稍后再试。');
+    }
+  }, []);
+
+  // 重建 LaunchServices 数据库耗时较长，仅在关联异常时由用户手动触发
+  const handleRebuild = useCallback(async () => {
+    setFeedback(null);
+    setError(null);
+    if (!window.confirm('修复关联会重建系统的应用注册数据库，可能需要几十秒，确定继续吗？')) {
+      return;
+    }
+    setLoading(true);
+    // lsregister 的输出逐行显示为进度
+    const unlisten = await listen<string>('launch-services-rebuild-progress', (event) => {
+      setFeedback(event.payload);
+    });
+    try {
+      const duration = await invoke<number>('rebuild_launch_services', { deep: false });
+      setFeedback(`已重建应用注册数据库，用时 ${(duration / 1000).toFixed(1)} 秒。`);
+      await fetchAssociations();
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setFeedback(null);
+      setError(message || '修复关联失败，请稍后再试。');
+    } finally {
+      unlisten();
+      setLoading(false);
+    }
+  }, [fetchAssociations]);
+
+  const handleVerify = useCallback(async (item: FileAssociation) => {
+    setFeedback(null);
+    setError(null);
+    try {
+      const result = await invoke<AssociationVerification>('verify_association', {
+        extension: item.extension,
+      });
+      if (result.effective) {
+        setFeedback(`.${result.extension} 的默认应用设置已生效。`);
+      } else {
+        setError(
+          `.${result.extension} 的设置未生效：期望 ${result.expectedBundleId}，系统实际使用 ${
+            result.actualBundleId ?? '(无)'
+          }。可以点击“修复关联”重建应用注册数据库。`,
+        );
+      }
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '验证默认应用失败，请稍后再试。');
+    }
+  }, []);
+
+  // 清除所有自定义的默认应用，可选地把列表恢复为内置扩展名
+  const handleResetAll = useCallback(async () => {
+    setFeedback(null);
+    setError(null);
+    if (!window.confirm('确定将列表中所有文件类型恢复为系统默认应用吗？修改前会自动备份。')) {
+      return;
+    }
+    const clearExtensions = window.confirm(
+      '是否同时移除自行添加的文件类型？选择“取消”则保留当前列表。',
+    );
+    setLoading(true);
+    try {
+      const result = await invoke<FileAssociation[]>('reset_all_to_system_defaults', {
+        clearExtensions,
+      });
+      setAssociations(sortAssociations(result));
+      setFeedback('已将所有文件类型恢复为系统默认应用。');
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '恢复系统默认失败，请稍后再试。');
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  const handleRemoveExtension = useCallback(async (extension: string) => {
+    setFeedback(null);
+    setError(null);
+    if (!window.confirm(`确定从列表中移除 .${extension} 吗？`)) {
+      return;
+    }
+    const clearHandler = window.confirm(
+      `是否同时清除为 .${extension} 设置的默认应用？选择“取消”则保留当前设置。`,
+    );
+    setLoading(true);
+    try {
+      const result = await invoke<RemoveExtensionResult>('remove_extension_and_handler', {
+        extension,
+        clearHandler,
+      });
+      setAssociations(sortAssociations(result.associations));
+      setFeedback(
+        result.handlerRemoved
+          ? `已移除 .${extension} 文件类型，并清除了它的默认应用设置。`
+          : `已移除 .${extension} 文件类型。`,
+      );
+    } catch (err) {
+      console.error(err);
+      const message =
+        typeof err === 'string' ? err : err instanceof Error ? err.message : JSON.stringify(err);
+      setError(message || '移除文件类型失败，请稍后再试。');
+    } finally {
+      setLoading(false);
+    }
+  }, []);
 
   const handleAddExtensionKey = useCallback(
     (event: KeyboardEvent<HTMLInputElement>) => {
@@ -228,6 +702,9 @@ export default function App() {
         <p>
           应用需要读取系统的文件关联信息，请在 macOS 系统设置中开启“完全磁盘访问”权限。
         </p>
+        {permission === 'indeterminate' && (
+          <p>未能找到用于检测权限的系统文件，无法确认是否已授权。如果已经开启，可以直接继续。</p>
+        )}
       </div>
       {error && <div className="refresh-banner" style={{ color: '#dc2626' }}>{error}</div>}
       {feedback && <div className="refresh-banner">{feedback}</div>}
@@ -238,6 +715,11 @@ export default function App() {
         <button className="button button-secondary" onClick={handleRefreshPermission}>
           我已授权，重新检测
         </button>
+        {permission === 'indeterminate' && (
+          <button className="button button-secondary" onClick={handleContinueAnyway}>
+            直接继续
+          </button>
+        )}
       </div>
     </div>
   );
@@ -251,6 +733,12 @@ export default function App() {
           value={query}
           onChange={(e) => setQuery(e.target.value)}
         />
+        <button className="button button-secondary" onClick={handleRebuild}>
+          修复关联
+        </button>
+        <button className="button button-secondary" onClick={handleResetAll}>
+          全部恢复默认
+        </button>
       </div>
       <div className="list-header">
         <span>文件类型</span>
@@ -266,22 +754,80 @@ export default function App() {
             if (!q) return true;
             return (
               item.extension.toLowerCase().includes(q) ||
-              item.applicationName.toLowerCase().includes(q)
+              item.applicationName.toLowerCase().includes(q) ||
+              item.category.toLowerCase().includes(q)
             );
           })
           .map((item) => (
           <div className="list-row" key={item.extension}>
-            <span className="extension-pill">.{item.extension}</span>
+            <span
+              className="extension-pill"
+              title={item.contentType ? `${item.category} · ${item.contentType}` : item.category}
+            >
+              .{item.extension}
+            </span>
             <div className="app-name">
-              <span>{item.applicationName}</span>
-              <span>{item.applicationPath}</span>
+              <span>
+                {item.applicationName}
+                {item.isUserOverride && (
+                  <em
+                    className="source-badge"
+                    title={item.systemDefaultName ? `系统默认：${item.systemDefaultName}` : undefined}
+                  >
+                    已自定义
+                  </em>
+                )}
+              </span>
+              {item.note && <span className="app-note">{item.note}</span>}
+              {(item.viewerBundleId || item.editorBundleId) && (
+                <span className="app-note">
+                  {[
+                    item.viewerBundleId && `查看：${item.viewerBundleId}`,
+                    item.editorBundleId && `编辑：${item.editorBundleId}`,
+                  ]
+                    .filter(Boolean)
+                    .join('，')}
+                </span>
+              )}
+              {statusHints[item.status] ? (
+                <span className="status-warning">{statusHints[item.status]}</span>
+              ) : (
+                <span>{item.applicationPath}</span>
+              )}
+            </div>
+            <div className="row-actions">
+              <button
+                className="button button-secondary modify-button"
+                onClick={() => handleModify(item.extension)}
+              >
+                修改默认应用
+              </button>
+              {item.applicationPath && item.status !== 'appNotFound' && (
+                <button className="row-link" onClick={() => handleReveal(item)}>
+                  在访达中显示
+                </button>
+              )}
+              {item.isUserOverride && (
+                <button className="row-link" onClick={() => handleVerify(item)}>
+                  验证是否生效
+                </button>
+              )}
+              <button className="row-link" onClick={() => handleEditNote(item)}>
+                {item.note ? '编辑备注' : '添加备注'}
+              </button>
+              <button className="row-link" onClick={() => handleTogglePin(item)}>
+                {item.isPinned ? '取消固定' : '固定到顶部'}
+              </button>
+              <button className="row-link" onClick={() => handleReset(item.extension)}>
+                恢复系统默认
+              </button>
+              <button
+                className="row-link row-link-danger"
+                onClick={() => handleRemoveExtension(item.extension)}
+              >
+                从列表移除
+              </button>
             </div>
-            <button
-              className="button button-secondary modify-button"
-              onClick={() => handleModify(item.extension)}
-            >
-              修改默认应用
-            </button>
           </div>
           ))
       ) : (
@@ -306,12 +852,17 @@ export default function App() {
         <>
           {feedback && <div className="refresh-banner">{feedback}</div>}
           {error && <div className="refresh-banner" style={{ color: '#dc2626' }}>{error}</div>}
+          {dutiMissing && (
+            <div className="refresh-banner">
+              未检测到 duti，部分不常见的扩展名可能无法设置默认应用。可在终端运行 brew install duti 安装。
+            </div>
+          )}
           <div className="refresh-banner" style={{ borderStyle: 'dashed' }}>
             <span>
               如果系统设置有变更，请点击按钮重新载入列表。
             </span>
             <div style={{ display: 'flex', gap: '0.5rem', flexWrap: 'wrap' }}>
-              <button onClick={fetchAssociations} disabled={loading}>
+              <button onClick={handleRefreshList} disabled={loading}>
                 刷新列表
               </button>
               <div className="add-extension-form">
@@ -319,7 +870,7 @@ export default function App() {
                   value={newExtension}
                   onChange={(event) => setNewExtension(event.target.value)}
                   onKeyDown={handleAddExtensionKey}
-                  placeholder="添加扩展名并设置默认应用 (例如 md)"
+                  placeholder="添加扩展名并设置默认应用 (例如 md，多个用空格分隔)"
                   disabled={loading}
                 />
                 <button onClick={handleAddExtension} disabled={loading}>